@@ -77,7 +77,7 @@ impl RandomInput {
         let len_normal_keys = normal_keys.len();
         let len_special_keys = special_keys.len();
         if self.rng.gen_range(0, len_normal_keys + len_special_keys) < len_normal_keys {
-            let b = normal_keys[self.rng.gen_range(0, len_normal_keys)].clone();
+            let b = normal_keys[self.rng.gen_range(0, len_normal_keys)];
             KeySeq::Key(b)
         } else {
             special_keys[self.rng.gen_range(0, len_special_keys)].clone()
@@ -170,7 +170,7 @@ fn no_term_edit_1000_operations_to_10000_chars_plain_text(b: &mut Bencher) {
 
 #[bench]
 fn with_term_edit_1000_operations_to_editor_rs(b: &mut Bencher) -> Result<()> {
-    let f = BufReader::new(File::open(&Path::new("src/editor.rs"))?);
+    let f = BufReader::new(File::open(Path::new("src/editor.rs"))?);
     let lines = f.lines().collect::<io::Result<Vec<_>>>()?;
     let input = RandomInput::new(1000);
     let _stdin = StdinRawMode::new()?;
@@ -185,7 +185,7 @@ fn with_term_edit_1000_operations_to_editor_rs(b: &mut Bencher) -> Result<()> {
 
 #[bench]
 fn no_term_edit_1000_operations_to_editor_rs(b: &mut Bencher) -> Result<()> {
-    let f = BufReader::new(File::open(&Path::new("src/editor.rs"))?);
+    let f = BufReader::new(File::open(Path::new("src/editor.rs"))?);
     let lines = f.lines().collect::<io::Result<Vec<_>>>()?;
     let input = RandomInput::new(1000);
     b.iter(|| {
@@ -196,3 +196,21 @@ fn no_term_edit_1000_operations_to_editor_rs(b: &mut Bencher) -> Result<()> {
     });
     Ok(())
 }
+
+// Type characters in the middle of a large Rust file. Every keystroke highlights the screen again
+#[bench]
+fn no_term_type_100_chars_in_middle_of_highlight_rs(b: &mut Bencher) -> Result<()> {
+    let f = BufReader::new(File::open(Path::new("src/highlight.rs"))?);
+    let lines = f.lines().collect::<io::Result<Vec<_>>>()?;
+    let page_downs = lines.len() / 2 / 24;
+    let mut input = vec![];
+    input.extend((0..page_downs).map(|_| InputSeq::new(KeySeq::PageDownKey)));
+    input.extend((0..100).map(|_| InputSeq::new(KeySeq::Key(b'x'))));
+    b.iter(|| {
+        let input = input.iter().cloned().map(Ok);
+        let mut editor = Editor::with_lines(lines.iter(), input, Discard, Some((80, 24))).unwrap();
+        editor.set_lang(Language::Rust);
+        editor.edit().unwrap();
+    });
+    Ok(())
+}
@@ -0,0 +1,66 @@
+#![feature(test)]
+
+extern crate test;
+
+use kiro_editor::{Highlighting, Language, TextBuffer};
+use test::Bencher;
+
+fn bench_highlight(b: &mut Bencher, path: &str, lang: Language) {
+    let buf = TextBuffer::open(path).unwrap();
+    let rows = buf.rows();
+    b.iter(|| {
+        let mut hl = Highlighting::new(lang, rows);
+        hl.update(rows, rows.len());
+        hl
+    });
+}
+
+#[bench]
+fn highlight_rust_source(b: &mut Bencher) {
+    bench_highlight(b, "src/highlight.rs", Language::Rust);
+}
+
+#[bench]
+fn highlight_rust_source_small(b: &mut Bencher) {
+    bench_highlight(b, "src/history.rs", Language::Rust);
+}
+
+// Highlight again after a character is typed in the middle of a large file
+#[bench]
+fn rehighlight_after_keystroke(b: &mut Bencher) {
+    let buf = TextBuffer::open("src/highlight.rs").unwrap();
+    let rows = buf.rows();
+    let mut hl = Highlighting::new(Language::Rust, rows);
+    hl.update(rows, rows.len());
+    b.iter(|| {
+        hl.invalidate_line(rows.len() / 2);
+        hl.update(rows, rows.len());
+    });
+}
+
+// Highlight all lines again though their highlights are not changed
+#[bench]
+fn rehighlight_unchanged_lines(b: &mut Bencher) {
+    let buf = TextBuffer::open("src/highlight.rs").unwrap();
+    let rows = buf.rows();
+    let mut hl = Highlighting::new(Language::Rust, rows);
+    hl.update(rows, rows.len());
+    b.iter(|| {
+        hl.force_refresh();
+        hl.update(rows, rows.len());
+    });
+}
+
+// Highlight a large file page by page as scrolling down from the top
+#[bench]
+fn highlight_while_scrolling(b: &mut Bencher) {
+    let buf = TextBuffer::open("src/highlight.rs").unwrap();
+    let rows = buf.rows();
+    b.iter(|| {
+        let mut hl = Highlighting::new(Language::Rust, rows);
+        for bottom in (0..rows.len()).step_by(50) {
+            hl.update(rows, bottom + 50);
+        }
+        hl
+    });
+}
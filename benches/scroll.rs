@@ -72,7 +72,7 @@ impl Write for Discard {
 
 #[bench]
 fn with_term_scroll_up_down_plain_text(b: &mut Bencher) -> Result<()> {
-    let f = BufReader::new(File::open(&Path::new("README.md"))?);
+    let f = BufReader::new(File::open(Path::new("README.md"))?);
     let lines = f.lines().collect::<io::Result<Vec<_>>>()?;
     let input = ScrollInput::new(20);
     let _stdin = StdinRawMode::new()?;
@@ -86,7 +86,7 @@ fn with_term_scroll_up_down_plain_text(b: &mut Bencher) -> Result<()> {
 
 #[bench]
 fn with_term_scroll_up_down_rust_code(b: &mut Bencher) -> Result<()> {
-    let f = BufReader::new(File::open(&Path::new("src/editor.rs"))?);
+    let f = BufReader::new(File::open(Path::new("src/editor.rs"))?);
     let lines = f.lines().collect::<io::Result<Vec<_>>>()?;
     let input = ScrollInput::new(20);
     let _stdin = StdinRawMode::new()?;
@@ -101,7 +101,7 @@ fn with_term_scroll_up_down_rust_code(b: &mut Bencher) -> Result<()> {
 
 #[bench]
 fn no_term_scroll_up_down_plain_text(b: &mut Bencher) -> Result<()> {
-    let f = BufReader::new(File::open(&Path::new("README.md"))?);
+    let f = BufReader::new(File::open(Path::new("README.md"))?);
     let lines = f.lines().collect::<io::Result<Vec<_>>>()?;
     let input = ScrollInput::new(20);
     b.iter(|| {
@@ -114,7 +114,7 @@ fn no_term_scroll_up_down_plain_text(b: &mut Bencher) -> Result<()> {
 
 #[bench]
 fn no_term_scroll_up_down_rust_code(b: &mut Bencher) -> Result<()> {
-    let f = BufReader::new(File::open(&Path::new("src/editor.rs"))?);
+    let f = BufReader::new(File::open(Path::new("src/editor.rs"))?);
     let lines = f.lines().collect::<io::Result<Vec<_>>>()?;
     let input = ScrollInput::new(20);
     b.iter(|| {
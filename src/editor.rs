@@ -1,4 +1,5 @@
 use crate::error::Result;
+use crate::export;
 use crate::highlight::Highlighting;
 use crate::input::{InputSeq, KeySeq};
 use crate::language::Language;
@@ -362,9 +363,16 @@ where
             },
         }
 
-        if let Some(line) = self.buf_mut().finish_edit() {
-            self.hl.needs_update = true;
-            self.screen.set_dirty_start(line);
+        if let Some((start, end)) = self.buf_mut().finish_edit() {
+            for y in start..=end {
+                self.hl.invalidate_line(y);
+            }
+            self.screen.set_dirty_start(start);
+        }
+        let buf = &self.bufs[self.buf_idx];
+        let (cx, cy) = buf.cursor();
+        if let Some(y) = self.hl.highlight_matching_bracket(buf.rows(), cx, cy) {
+            self.screen.set_dirty_start(y);
         }
         if self.buf().cursor() != prev_cursor {
             self.screen.cursor_moved = true;
@@ -428,6 +436,20 @@ where
         buf.set_lang(lang);
         self.hl = Highlighting::new(lang, buf.rows());
     }
+
+    // Export entire text of current buffer as HTML colored in the same way as the screen
+    pub fn to_html(&mut self) -> String {
+        let rows = self.bufs[self.buf_idx].rows();
+        self.hl.update(rows, rows.len());
+        export::to_html(rows, &self.hl)
+    }
+
+    // Export entire text of current buffer as text colored with escape sequences for the terminal
+    pub fn to_ansi(&mut self) -> String {
+        let rows = self.bufs[self.buf_idx].rows();
+        self.hl.update(rows, rows.len());
+        export::to_ansi(rows, &self.hl, self.screen.term_color())
+    }
 }
 
 pub struct Edit<'a, I, W>
@@ -511,7 +533,7 @@ mod tests {
 
     fn sp(k: KeySeq) -> InputSeq {
         if let Key(_) = k {
-            assert!(false, "{:?}", k);
+            panic!("{:?}", k);
         }
         InputSeq::new(k)
     }
@@ -11,6 +11,8 @@ pub enum Error {
     UnknownWindowSize,
     NotUtf8Input(Vec<u8>),
     ControlCharInText(char),
+    InvalidSyntaxFile(usize, String),
+    InvalidRegex(String),
 }
 
 impl fmt::Display for Error {
@@ -33,6 +35,10 @@ impl fmt::Display for Error {
                 Ok(())
             }
             ControlCharInText(c) => write!(f, "Invalid character for text is included: {:?}", c),
+            InvalidSyntaxFile(line, msg) => {
+                write!(f, "Invalid syntax definition at line {}: {}", line, msg)
+            }
+            InvalidRegex(msg) => write!(f, "Invalid regular expression: {}", msg),
         }
     }
 }
@@ -0,0 +1,144 @@
+// Export highlighted text to other formats so that it can be shared outside the editor
+use crate::highlight::Highlighting;
+use crate::row::Row;
+use crate::term_color::{Color, Style, TermColor};
+
+// Background color of screen (gruvbox dark)
+const BACKGROUND_COLOR: &str = "#282828";
+
+fn push_html_escaped(out: &mut String, c: char) {
+    match c {
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '&' => out.push_str("&amp;"),
+        c => out.push(c),
+    }
+}
+
+fn hex(color: Color) -> String {
+    let (r, g, b) = color.rgb();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// Render rows as <pre> element. Characters are styled with <span> and consecutive characters
+// of the same style share one <span>
+pub fn to_html(rows: &[Row], hl: &Highlighting) -> String {
+    let mut out = format!(
+        "<pre style=\"color:{};background-color:{}\">",
+        hex(Color::Reset),
+        BACKGROUND_COLOR,
+    );
+
+    let normal = Style::from(Color::Reset);
+    for (y, row) in rows.iter().enumerate() {
+        let mut prev_style = normal;
+        for (c, h) in row.render_text().chars().zip(hl.line(y)) {
+            let style = hl.color_scheme().style(h);
+            if style != prev_style {
+                if prev_style != normal {
+                    out.push_str("</span>");
+                }
+                if style != normal {
+                    out.push_str("<span style=\"color:");
+                    out.push_str(&hex(style.color));
+                    if style.bold {
+                        out.push_str(";font-weight:bold");
+                    }
+                    if style.dim {
+                        out.push_str(";opacity:0.6");
+                    }
+                    if style.underline {
+                        out.push_str(";text-decoration:underline");
+                    }
+                    out.push_str("\">");
+                }
+                prev_style = style;
+            }
+            push_html_escaped(&mut out, c);
+        }
+        if prev_style != normal {
+            out.push_str("</span>");
+        }
+        out.push('\n');
+    }
+
+    out.push_str("</pre>\n");
+    out
+}
+
+// Render rows with color escape sequences in the same way as Screen renders them. Escape sequence
+// is only emitted when style changes and each line ends with reset sequence
+pub fn to_ansi(rows: &[Row], hl: &Highlighting, term: TermColor) -> String {
+    let mut out = vec![];
+    for (y, row) in rows.iter().enumerate() {
+        let mut prev_style = Style::from(Color::Reset);
+        for (c, h) in row.render_text().chars().zip(hl.line(y)) {
+            let style = hl.color_scheme().style(h);
+            if style != prev_style {
+                out.extend_from_slice(&term.switch_sequence(prev_style, style));
+                prev_style = style;
+            }
+            let mut b = [0; 4];
+            out.extend_from_slice(c.encode_utf8(&mut b).as_bytes());
+        }
+        out.extend_from_slice(&term.sequence(Color::Reset));
+        out.push(b'\n');
+    }
+    // All sequences and text are valid UTF-8
+    String::from_utf8(out).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::Language;
+
+    fn highlight(lang: Language, lines: &[&str]) -> (Vec<Row>, Highlighting) {
+        let rows: Vec<_> = lines.iter().map(|l| Row::new(*l).unwrap()).collect();
+        let mut hl = Highlighting::new(lang, &rows);
+        hl.update(&rows, rows.len());
+        (rows, hl)
+    }
+
+    #[test]
+    fn html() {
+        let (rows, hl) = highlight(Language::Rust, &["fn <&>", "let"]);
+        let keyword = hex(hl
+            .color_scheme()
+            .color(crate::highlight::Highlight::Keyword));
+        let operator = hex(hl
+            .color_scheme()
+            .color(crate::highlight::Highlight::Operator));
+        let expected = format!(
+            "<pre style=\"color:#fbf1c7;background-color:#282828\">\
+             <span style=\"color:{k}\">fn</span> <span style=\"color:{o}\">&lt;&amp;&gt;</span>\n\
+             <span style=\"color:{k}\">let</span>\n\
+             </pre>\n",
+            k = keyword,
+            o = operator,
+        );
+        assert_eq!(to_html(&rows, &hl), expected);
+    }
+
+    #[test]
+    fn ansi() {
+        let (rows, hl) = highlight(Language::Rust, &["x = \"a\";"]);
+        let expected = "x \x1b[33m=\x1b[39;0m \x1b[32m\"a\"\x1b[39;0m;\x1b[39;0m\n";
+        assert_eq!(to_ansi(&rows, &hl, TermColor::Colors16), expected);
+    }
+
+    #[test]
+    fn ansi_bold_keyword() {
+        use crate::highlight::{ColorScheme, Highlight};
+
+        let (rows, mut hl) = highlight(Language::Rust, &["fn f"]);
+        let mut scheme = ColorScheme::default();
+        scheme.set_style(Highlight::Keyword, Color::Blue.bold());
+        hl.set_color_scheme(scheme);
+        let expected = "\x1b[94m\x1b[1mfn\x1b[22m\x1b[39;0m \x1b[93mf\x1b[39;0m\n";
+        assert_eq!(to_ansi(&rows, &hl, TermColor::Colors16), expected);
+
+        let html = to_html(&rows, &hl);
+        assert!(html.contains("<span style=\"color:#83a598;font-weight:bold\">fn</span>"));
+    }
+}
@@ -1,22 +1,51 @@
+use std::borrow::Cow;
+use std::cmp;
+use std::collections::HashMap;
 use std::iter;
+use std::mem;
+use std::sync::Arc;
 
+use crate::error::{Error, Result};
 use crate::language::Language;
 use crate::row::Row;
-use crate::term_color::Color;
+use crate::syntax_file::{self, Value};
+use crate::term_color::{Color, Style};
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Highlight {
     Normal,
     Number,
     String,
+    Key,
     Comment,
+    DocComment,
     Keyword,
     Type,
     Definition,
     Char,
+    Escape,
     Statement,
     Boolean,
     SpecialVar,
+    Constant,
+    Variable,
+    Symbol,
+    Operator,
+    Regex,
+    Function,
+    Builtin,
+    Macro,
+    Attribute,
+    Annotation,
+    Lifetime,
+    Label,
+    Preprocessor,
+    Heading,
+    Emphasis,
+    Todo,
+    Link,
+    TrailingWhitespace,
+    Bracket,
     Search,
     Match,
 }
@@ -29,36 +58,216 @@ impl Highlight {
             Normal => Reset,
             Number => Purple,
             String => Green,
+            Key => Cyan,
             Comment => Gray,
+            DocComment => Green,
             Keyword => Blue,
             Type => Orange,
             Definition => Yellow,
             Char => Green,
+            Escape => Purple,
             Statement => Red,
             Boolean => Purple,
             SpecialVar => Cyan,
+            Constant => Purple,
+            Variable => Cyan,
+            Symbol => Purple,
+            Operator => Orange,
+            Regex => Cyan,
+            Function => Cyan,
+            Builtin => Orange,
+            Macro => Purple,
+            Attribute => Cyan,
+            Annotation => Yellow,
+            Lifetime => Orange,
+            Label => Purple,
+            Preprocessor => Purple,
+            Heading => Blue,
+            Emphasis => Orange,
+            Todo => Yellow,
+            Link => Blue,
+            TrailingWhitespace => RedBG,
+            Bracket => Invert,
             Search => OrangeBG,
             Match => YellowBG,
         }
     }
+
+    pub fn style(self) -> Style {
+        use Highlight::*;
+        match self {
+            Heading | Todo => self.color().bold(),
+            Link => self.color().underline(),
+            _ => self.color().into(),
+        }
+    }
+}
+
+// Number of variants of Highlight. Note that Highlight::Match must be the last variant
+const NUM_HIGHLIGHTS: usize = Highlight::Match as usize + 1;
+
+// Mapping from highlight to style. Highlights which are not set use the default styles
+#[derive(Clone)]
+pub struct ColorScheme {
+    styles: [Option<Style>; NUM_HIGHLIGHTS],
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        ColorScheme {
+            styles: [None; NUM_HIGHLIGHTS],
+        }
+    }
+}
+
+impl ColorScheme {
+    pub fn set(&mut self, hl: Highlight, color: Color) {
+        self.set_style(hl, color.into());
+    }
+
+    pub fn set_style(&mut self, hl: Highlight, style: Style) {
+        self.styles[hl as usize] = Some(style);
+    }
+
+    pub fn style(&self, hl: Highlight) -> Style {
+        self.styles[hl as usize].unwrap_or_else(|| hl.style())
+    }
+
+    pub fn color(&self, hl: Highlight) -> Color {
+        self.style(hl).color
+    }
+}
+
+// Quote of string literal and how the content of the literal is scanned
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct StringKind {
+    quote: char,
+    escapes: bool,   // Escape sequences and variables are processed
+    multiline: bool, // String literal can span multiple lines
+}
+
+impl StringKind {
+    pub const fn new(quote: char) -> Self {
+        Self {
+            quote,
+            escapes: true,
+            multiline: false,
+        }
+    }
+
+    // No escape nor variable is processed such as '...' in shell
+    pub const fn raw(self) -> Self {
+        Self {
+            escapes: false,
+            ..self
+        }
+    }
+
+    pub const fn multiline(self) -> Self {
+        Self {
+            multiline: true,
+            ..self
+        }
+    }
+}
+
+// List of words such as keywords. Builtin syntaxes refer to static lists while syntaxes loaded
+// from file or built at runtime own their lists
+enum Words {
+    Static(&'static [&'static str]),
+    Owned(Vec<String>),
 }
 
-struct SyntaxHighlight {
+impl Words {
+    fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        let (words, owned) = match self {
+            Words::Static(words) => (*words, &[][..]),
+            Words::Owned(owned) => (&[][..], owned.as_slice()),
+        };
+        words
+            .iter()
+            .copied()
+            .chain(owned.iter().map(String::as_str))
+    }
+}
+
+// Fields which can be set by syntax definition file own their values in syntax loaded at runtime.
+// Syntaxes are shared with `Arc` so that reloading a definition drops the previous one once no
+// buffer uses it
+pub struct SyntaxHighlight {
     lang: Language,
-    string_quotes: &'static [char],
+    string_quotes: Cow<'static, [StringKind]>,
+    triple_quotes: &'static [char],
+    line_continuation: bool,
+    string_prefixes: &'static [&'static str],
+    interpolated_prefixes: &'static [&'static str],
+    raw_string: bool,
+    line_strings: Option<&'static str>,
+    long_brackets: bool,
+    delimited_raw_string: bool,
+    verbatim_strings: bool,
+    object_keys: bool,
+    mapping_keys: Option<&'static [char]>, // Separators of key and value
+    table_headers: bool,
     number: bool,
     hex_number: bool,
     bin_number: bool,
+    oct_number: bool,
     number_delim: Option<char>,
+    number_suffixes: &'static [&'static str],
+    immediate_prefixes: &'static [char],
+    hex_float: bool,
+    leading_decimal_point: bool,
+    datetime: bool,
     character: bool,
-    line_comment: Option<&'static str>,
-    block_comment: Option<(&'static str, &'static str)>,
-    keywords: &'static [&'static str],
-    control_statements: &'static [&'static str],
-    builtin_types: &'static [&'static str],
+    lifetime: bool,
+    symbol: bool,
+    regex_literal: bool,
+    sigils: bool,
+    quote_operators: &'static [&'static str],
+    operators: &'static [&'static str],
+    decorator: bool,
+    annotation_sigil: Option<char>,
+    at_builtins: bool,
+    at_macros: bool,
+    attribute: bool,
+    preprocessor: bool,
+    hash_directives: bool,
+    hex_color: bool,
+    property_names: bool,
+    field_names: bool,
+    markup: bool,
+    markdown: bool,
+    makefile: bool,
+    labels: bool,
+    statement_labels: bool,
+    dot_directives: bool,
+    dollar_variables: bool,
+    variable_scopes: &'static [&'static str],
+    sigil_variables: bool,
+    paren_interpolation: bool,
+    hash_interpolation: bool,
+    anchors: bool,
+    line_comments: Words,
+    statement_comment: Option<&'static str>,
+    block_comment: Option<(Cow<'static, str>, Cow<'static, str>)>,
+    nested_block_comment: bool,
+    doc_line_comments: &'static [&'static str],
+    doc_block_comment: Option<&'static str>,
+    line_head_block_comment: Option<(&'static str, &'static str)>,
+    keywords: Words,
+    control_statements: Words,
+    builtin_types: Words,
+    builtin_functions: Words,
     boolean_constants: &'static [&'static str],
     special_vars: &'static [&'static str],
     definition_keywords: &'static [&'static str],
+    label_keywords: &'static [&'static str],
+    case_insensitive_keywords: bool,
+    highlight_functions: bool,
+    highlight_screaming_case: bool,
+    highlight_capitalized_types: bool,
+    macro_bang: bool,
 }
 
 const PLAIN_SYNTAX: SyntaxHighlight = SyntaxHighlight {
@@ -66,17 +275,75 @@ const PLAIN_SYNTAX: SyntaxHighlight = SyntaxHighlight {
     number: false,
     hex_number: false,
     bin_number: false,
+    oct_number: false,
     number_delim: None,
-    string_quotes: &[],
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
     character: false,
-    line_comment: None,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&[]),
+    statement_comment: None,
     block_comment: None,
-    keywords: &[],
-    control_statements: &[],
-    builtin_types: &[],
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[]),
+    control_statements: Words::Static(&[]),
+    builtin_types: Words::Static(&[]),
+    builtin_functions: Words::Static(&[]),
     boolean_constants: &[],
     special_vars: &[],
     definition_keywords: &[],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: false,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
 };
 
 const C_SYNTAX: SyntaxHighlight = SyntaxHighlight {
@@ -84,25 +351,90 @@ const C_SYNTAX: SyntaxHighlight = SyntaxHighlight {
     number: true,
     hex_number: true,
     bin_number: false,
+    oct_number: false,
     number_delim: None,
-    string_quotes: &['"'],
+    number_suffixes: &[
+        "u", "l", "ul", "lu", "ll", "ull", "llu", "f", "U", "L", "UL", "LU", "LL", "ULL", "LLU",
+        "F",
+    ],
+    immediate_prefixes: &[],
+    hex_float: true,
+    leading_decimal_point: true,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"')]),
+    triple_quotes: &[],
+    line_continuation: true,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
     character: true,
-    line_comment: Some("//"),
-    block_comment: Some(("/*", "*/")),
-    keywords: &[
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        "<<=", ">>=", "->", "++", "--", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "+=", "-=",
+        "*=", "/=", "%=", "&=", "|=", "^=", "+", "-", "*", "/", "%", "<", ">", "=", "!", "&", "|",
+        "^", "~", "?", ":",
+    ],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: true,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: true,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["//"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("/*"), Cow::Borrowed("*/"))),
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
         "auto", "const", "enum", "extern", "inline", "register", "restrict", "sizeof", "static",
         "struct", "typedef", "union", "volatile",
-    ],
-    control_statements: &[
+    ]),
+    control_statements: Words::Static(&[
         "break", "case", "continue", "default", "do", "else", "for", "goto", "if", "return",
         "switch", "while",
-    ],
-    builtin_types: &[
+    ]),
+    builtin_types: Words::Static(&[
         "char", "double", "float", "int", "long", "short", "signed", "unsigned", "void",
-    ],
+    ]),
+    builtin_functions: Words::Static(&[]),
     boolean_constants: &[],
     special_vars: &[],
     definition_keywords: &["enum", "struct", "union"],
+    label_keywords: &["goto"],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: true,
+    highlight_capitalized_types: false,
+    macro_bang: false,
 };
 
 const RUST_SYNTAX: SyntaxHighlight = SyntaxHighlight {
@@ -110,42 +442,162 @@ const RUST_SYNTAX: SyntaxHighlight = SyntaxHighlight {
     number: true,
     hex_number: true,
     bin_number: true,
+    oct_number: true,
     number_delim: Some('_'),
-    string_quotes: &['"'],
+    number_suffixes: &[
+        "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+        "f32", "f64",
+    ],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"').multiline()]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &["b"],
+    interpolated_prefixes: &[],
+    raw_string: true,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
     character: true,
-    line_comment: Some("//"),
-    block_comment: Some(("/*", "*/")),
-    keywords: &[
+    lifetime: true,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        "<<=", ">>=", "..=", "::", "->", "=>", "..", "==", "!=", "<=", ">=", "&&", "||", "+=",
+        "-=", "*=", "/=", "%=", "&=", "|=", "^=", "<<", ">>", "+", "-", "*", "/", "%", "<", ">",
+        "=", "!", "&", "|", "^", "?",
+    ],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: true,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: true,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["//"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("/*"), Cow::Borrowed("*/"))),
+    nested_block_comment: true,
+    doc_line_comments: &["///", "//!"],
+    doc_block_comment: Some("/**"),
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
         "as", "async", "await", "const", "crate", "dyn", "enum", "extern", "fn", "impl", "let",
         "mod", "move", "mut", "pub", "ref", "Self", "static", "struct", "super", "trait", "type",
         "union", "unsafe", "use", "where",
-    ],
-    control_statements: &[
+    ]),
+    control_statements: Words::Static(&[
         "break", "continue", "else", "for", "if", "in", "loop", "match", "return", "while",
-    ],
-    builtin_types: &[
+    ]),
+    builtin_types: Words::Static(&[
         "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
         "f32", "f64", "bool", "char", "Box", "Option", "Some", "None", "Result", "Ok", "Err",
         "String", "Vec",
-    ],
+    ]),
+    builtin_functions: Words::Static(&[]),
     boolean_constants: &["true", "false"],
     special_vars: &["self"],
     definition_keywords: &[
         "fn", "let", "const", "mod", "struct", "enum", "trait", "union",
     ],
+    label_keywords: &["break", "continue"],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: true,
+    highlight_capitalized_types: false,
+    macro_bang: true,
 };
 
 const JAVASCRIPT_SYNTAX: SyntaxHighlight = SyntaxHighlight {
     lang: Language::JavaScript,
     number: true,
     hex_number: true,
-    bin_number: false,
+    bin_number: true,
+    oct_number: true,
     number_delim: None,
-    string_quotes: &['"', '\''],
+    number_suffixes: &["n"],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: true,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"'), StringKind::new('\'')]),
+    triple_quotes: &[],
+    line_continuation: true,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
     character: false,
-    line_comment: Some("//"),
-    block_comment: Some(("/*", "*/")),
-    keywords: &[
+    lifetime: false,
+    symbol: false,
+    regex_literal: true,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        "===", "!==", "**=", "<<=", ">>=", ">>>", "=>", "==", "!=", "<=", ">=", "&&", "||", "??",
+        "?.", "++", "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "**", "<<", ">>", "+",
+        "-", "*", "/", "%", "<", ">", "=", "!", "&", "|", "^", "~", "?",
+    ],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["//"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("/*"), Cow::Borrowed("*/"))),
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
         "class",
         "const",
         "debugger",
@@ -164,12 +616,12 @@ const JAVASCRIPT_SYNTAX: SyntaxHighlight = SyntaxHighlight {
         "void",
         "with",
         "yield",
-    ],
-    control_statements: &[
+    ]),
+    control_statements: Words::Static(&[
         "break", "case", "catch", "continue", "default", "do", "else", "finally", "for", "if",
         "return", "switch", "throw", "try", "while",
-    ],
-    builtin_types: &[
+    ]),
+    builtin_types: Words::Static(&[
         "Object",
         "Function",
         "Boolean",
@@ -201,14 +653,205 @@ const JAVASCRIPT_SYNTAX: SyntaxHighlight = SyntaxHighlight {
         "Generator",
         "GeneratorFunction",
         "AsyncFunction",
-        "Refrect",
+        "Reflect",
         "Proxy",
         "Intl",
         "WebAssembly",
-    ],
+    ]),
+    builtin_functions: Words::Static(&[]),
     boolean_constants: &["true", "false"],
     special_vars: &["null", "this", "undefined"],
     definition_keywords: &["class", "const", "function", "var", "let"],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: true,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const TYPESCRIPT_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::TypeScript,
+    number: true,
+    hex_number: true,
+    bin_number: true,
+    oct_number: true,
+    number_delim: Some('_'),
+    number_suffixes: &["n"],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: true,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[
+        StringKind::new('"'),
+        StringKind::new('\''),
+        StringKind::new('`').multiline(),
+    ]),
+    triple_quotes: &[],
+    line_continuation: true,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: true,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        "===", "!==", "**=", "<<=", ">>=", ">>>", "=>", "==", "!=", "<=", ">=", "&&", "||", "??",
+        "?.", "++", "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "**", "<<", ">>", "+",
+        "-", "*", "/", "%", "<", ">", "=", "!", "&", "|", "^", "~", "?",
+    ],
+    decorator: false,
+    annotation_sigil: Some('@'),
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["//"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("/*"), Cow::Borrowed("*/"))),
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "abstract",
+        "as",
+        "async",
+        "await",
+        "class",
+        "const",
+        "debugger",
+        "declare",
+        "delete",
+        "enum",
+        "export",
+        "extends",
+        "function",
+        "implements",
+        "import",
+        "in",
+        "instanceof",
+        "interface",
+        "is",
+        "keyof",
+        "let",
+        "namespace",
+        "new",
+        "of",
+        "private",
+        "protected",
+        "public",
+        "readonly",
+        "static",
+        "super",
+        "type",
+        "typeof",
+        "var",
+        "with",
+        "yield",
+    ]),
+    control_statements: Words::Static(&[
+        "break", "case", "catch", "continue", "default", "do", "else", "finally", "for", "if",
+        "return", "switch", "throw", "try", "while",
+    ]),
+    builtin_types: Words::Static(&[
+        "any",
+        "bigint",
+        "boolean",
+        "never",
+        "number",
+        "object",
+        "string",
+        "symbol",
+        "unknown",
+        "void",
+        "Object",
+        "Function",
+        "Boolean",
+        "Symbol",
+        "Error",
+        "Number",
+        "BigInt",
+        "Math",
+        "Date",
+        "String",
+        "RegExp",
+        "Array",
+        "Int8Array",
+        "Int16Array",
+        "Int32Array",
+        "BigInt64Array",
+        "Uint8Array",
+        "Uint16Array",
+        "Uint32Array",
+        "BigUint64Array",
+        "Float32Array",
+        "Float64Array",
+        "ArrayBuffer",
+        "SharedArrayBuffer",
+        "Atomics",
+        "DataView",
+        "JSON",
+        "Promise",
+        "Generator",
+        "GeneratorFunction",
+        "AsyncFunction",
+        "Reflect",
+        "Proxy",
+        "Intl",
+        "WebAssembly",
+        "Partial",
+        "Readonly",
+        "Record",
+        "Pick",
+        "Omit",
+    ]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false"],
+    special_vars: &["null", "this", "undefined"],
+    definition_keywords: &[
+        "class",
+        "const",
+        "enum",
+        "function",
+        "interface",
+        "let",
+        "namespace",
+        "type",
+        "var",
+    ],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: true,
+    highlight_capitalized_types: false,
+    macro_bang: false,
 };
 
 const GO_SYNTAX: SyntaxHighlight = SyntaxHighlight {
@@ -216,12 +859,70 @@ const GO_SYNTAX: SyntaxHighlight = SyntaxHighlight {
     number: true,
     hex_number: true,
     bin_number: true,
+    oct_number: true,
     number_delim: Some('_'),
-    string_quotes: &['"', '`'],
+    number_suffixes: &["i"], // Imaginary literal
+    immediate_prefixes: &[],
+    hex_float: true,
+    leading_decimal_point: true,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[
+        StringKind::new('"'),
+        StringKind::new('`').raw().multiline(), // Raw string literal `...`
+    ]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
     character: true,
-    line_comment: Some("//"),
-    block_comment: Some(("/*", "*/")),
-    keywords: &[
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        "<<=", ">>=", "&^=", "...", ":=", "<-", "&^", "++", "--", "==", "!=", "<=", ">=", "&&",
+        "||", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "<<", ">>", "+", "-", "*", "/", "%",
+        "<", ">", "=", "!", "&", "|", "^",
+    ],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["//"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("/*"), Cow::Borrowed("*/"))),
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
         "chan",
         "const",
         "defer",
@@ -235,8 +936,8 @@ const GO_SYNTAX: SyntaxHighlight = SyntaxHighlight {
         "struct",
         "type",
         "var",
-    ],
-    control_statements: &[
+    ]),
+    control_statements: Words::Static(&[
         "break",
         "case",
         "continue",
@@ -249,8 +950,8 @@ const GO_SYNTAX: SyntaxHighlight = SyntaxHighlight {
         "return",
         "select",
         "switch",
-    ],
-    builtin_types: &[
+    ]),
+    builtin_types: Words::Static(&[
         "bool",
         "byte",
         "complex128",
@@ -271,9 +972,10 @@ const GO_SYNTAX: SyntaxHighlight = SyntaxHighlight {
         "uint64",
         "uint8",
         "uintptr",
-    ],
+    ]),
+    builtin_functions: Words::Static(&[]),
     boolean_constants: &["true", "false"],
-    special_vars: &["nil"],
+    special_vars: &["nil", "iota"],
     definition_keywords: &[
         "const",
         "func",
@@ -283,19 +985,83 @@ const GO_SYNTAX: SyntaxHighlight = SyntaxHighlight {
         "type",
         "var",
     ],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
 };
 
 const CPP_SYNTAX: SyntaxHighlight = SyntaxHighlight {
-    lang: Language::C,
+    lang: Language::Cpp,
     number: true,
     hex_number: true,
     bin_number: true,
+    oct_number: false,
     number_delim: Some('\''),
-    string_quotes: &['"'],
+    number_suffixes: &[
+        "u", "l", "ul", "lu", "ll", "ull", "llu", "f", "U", "L", "UL", "LU", "LL", "ULL", "LLU",
+        "F",
+    ],
+    immediate_prefixes: &[],
+    hex_float: true,
+    leading_decimal_point: true,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"')]),
+    triple_quotes: &[],
+    line_continuation: true,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: true,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
     character: true,
-    line_comment: Some("//"),
-    block_comment: Some(("/*", "*/")),
-    keywords: &[
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        "<=>", "<<=", ">>=", "::", "->", "++", "--", "<<", ">>", "<=", ">=", "==", "!=", "&&",
+        "||", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "+", "-", "*", "/", "%", "<", ">",
+        "=", "!", "&", "|", "^", "~", "?", ":",
+    ],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: true,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: true,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["//"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("/*"), Cow::Borrowed("*/"))),
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
         "alignas",
         "alignof",
         "and",
@@ -368,15 +1134,16 @@ const CPP_SYNTAX: SyntaxHighlight = SyntaxHighlight {
         "module",
         "transaction_safe",
         "transaction_safe_dynamic",
-    ],
-    control_statements: &[
+    ]),
+    control_statements: Words::Static(&[
         "break", "case", "catch", "continue", "default", "do", "else", "for", "goto", "if",
         "return", "switch", "throw", "try", "while",
-    ],
-    builtin_types: &[
+    ]),
+    builtin_types: Words::Static(&[
         "char", "char8_t", "char16_t", "char32_t", "double", "float", "int", "long", "short",
         "signed", "unsigned", "void", "wchar_t",
-    ],
+    ]),
+    builtin_functions: Words::Static(&[]),
     boolean_constants: &["true", "false"],
     special_vars: &["this"],
     definition_keywords: &[
@@ -388,6 +1155,12 @@ const CPP_SYNTAX: SyntaxHighlight = SyntaxHighlight {
         "union",
         "module",
     ],
+    label_keywords: &["goto"],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: true,
+    highlight_capitalized_types: false,
+    macro_bang: false,
 };
 
 const PYTHON_SYNTAX: SyntaxHighlight = SyntaxHighlight {
@@ -395,20 +1168,74 @@ const PYTHON_SYNTAX: SyntaxHighlight = SyntaxHighlight {
     number: true,
     hex_number: true,
     bin_number: true,
+    oct_number: true,
     number_delim: Some('_'),
-    string_quotes: &['"', '\''], // TODO: Multi-line strings '''...'''
+    number_suffixes: &["j", "J"],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: true,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"'), StringKind::new('\'')]),
+    triple_quotes: &['"', '\''],
+    line_continuation: true,
+    string_prefixes: &[
+        "r", "u", "b", "f", "br", "rb", "fr", "rf", "R", "U", "B", "F", "Br", "bR", "BR", "Rb",
+        "rB", "RB", "Fr", "fR", "FR", "Rf", "rF", "RF",
+    ],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
     character: false,
-    line_comment: Some("#"),
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[],
+    decorator: false,
+    annotation_sigil: Some('@'),
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["#"]),
+    statement_comment: None,
     block_comment: None,
-    keywords: &[
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
         "and", "as", "assert", "async", "await", "class", "def", "del", "from", "global", "import",
         "in", "is", "lambda", "nonlocal", "not", "or", "with",
-    ],
-    control_statements: &[
+    ]),
+    control_statements: Words::Static(&[
         "break", "continue", "elif", "else", "except", "finally", "for", "if", "pass", "raise",
         "return", "try", "while", "yield",
-    ],
-    builtin_types: &[
+    ]),
+    builtin_types: Words::Static(&[
         "int",
         "float",
         "complex",
@@ -422,480 +1249,8331 @@ const PYTHON_SYNTAX: SyntaxHighlight = SyntaxHighlight {
         "set",
         "frozenset",
         "dict",
-    ],
+    ]),
+    builtin_functions: Words::Static(&[
+        "__import__",
+        "abs",
+        "aiter",
+        "all",
+        "anext",
+        "any",
+        "ascii",
+        "bin",
+        "breakpoint",
+        "callable",
+        "chr",
+        "classmethod",
+        "compile",
+        "delattr",
+        "dir",
+        "divmod",
+        "enumerate",
+        "eval",
+        "exec",
+        "filter",
+        "format",
+        "getattr",
+        "globals",
+        "hasattr",
+        "hash",
+        "help",
+        "hex",
+        "id",
+        "input",
+        "isinstance",
+        "issubclass",
+        "iter",
+        "len",
+        "locals",
+        "map",
+        "max",
+        "min",
+        "next",
+        "object",
+        "oct",
+        "open",
+        "ord",
+        "pow",
+        "print",
+        "property",
+        "repr",
+        "reversed",
+        "round",
+        "setattr",
+        "slice",
+        "sorted",
+        "staticmethod",
+        "sum",
+        "super",
+        "type",
+        "vars",
+        "zip",
+    ]),
     boolean_constants: &["True", "False"],
     special_vars: &["self", "None"],
     definition_keywords: &["def", "class", "global", "nonlocal"],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: true,
+    highlight_capitalized_types: false,
+    macro_bang: false,
 };
 
-impl SyntaxHighlight {
-    fn for_lang(lang: Language) -> &'static SyntaxHighlight {
-        use Language::*;
-        match lang {
-            Plain => &PLAIN_SYNTAX,
-            C => &C_SYNTAX,
-            Rust => &RUST_SYNTAX,
-            JavaScript => &JAVASCRIPT_SYNTAX,
-            Go => &GO_SYNTAX,
-            Cpp => &CPP_SYNTAX,
-            Python => &PYTHON_SYNTAX,
-        }
-    }
-}
+const RUBY_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Ruby,
+    number: true,
+    hex_number: true,
+    bin_number: true,
+    oct_number: true,
+    number_delim: Some('_'),
+    number_suffixes: &["r", "i", "ri"],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[
+        StringKind::new('"').multiline(),
+        StringKind::new('\'').multiline(),
+        StringKind::new('`').multiline(),
+    ]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: true,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["#"]),
+    statement_comment: None,
+    block_comment: None,
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: Some(("=begin", "=end")),
+    keywords: Words::Static(&[
+        "BEGIN", "END", "alias", "and", "begin", "class", "def", "do", "end", "ensure", "module",
+        "not", "or", "then", "undef",
+    ]),
+    control_statements: Words::Static(&[
+        "break", "case", "else", "elsif", "for", "if", "in", "next", "redo", "rescue", "retry",
+        "return", "unless", "until", "when", "while", "yield",
+    ]),
+    builtin_types: Words::Static(&[
+        "Array",
+        "Comparable",
+        "Enumerable",
+        "Exception",
+        "File",
+        "Float",
+        "Hash",
+        "IO",
+        "Integer",
+        "Kernel",
+        "Numeric",
+        "Object",
+        "Proc",
+        "Range",
+        "Regexp",
+        "StandardError",
+        "String",
+        "Struct",
+        "Symbol",
+    ]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false"],
+    special_vars: &[
+        "nil",
+        "self",
+        "super",
+        "__FILE__",
+        "__LINE__",
+        "__ENCODING__",
+    ],
+    definition_keywords: &["def", "class", "module"],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: true,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
 
-#[derive(PartialEq, Clone, Copy)]
-enum NumLit {
-    Digit,
-    Hex,
-    Bin,
-}
+const CSS_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Css,
+    number: true,
+    hex_number: false,
+    bin_number: false,
+    oct_number: false,
+    number_delim: None,
+    number_suffixes: &[
+        "px", "em", "rem", "ex", "ch", "vw", "vh", "vmin", "vmax", "cm", "mm", "in", "pt", "pc",
+        "deg", "rad", "turn", "s", "ms", "hz", "khz", "dpi", "fr", "%",
+    ],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: true,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"'), StringKind::new('\'')]),
+    triple_quotes: &[],
+    line_continuation: true,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[],
+    decorator: true,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: true,
+    property_names: true,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&[]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("/*"), Cow::Borrowed("*/"))),
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&["important", "inherit", "initial", "unset", "auto", "none"]),
+    control_statements: Words::Static(&[]),
+    builtin_types: Words::Static(&[]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &[],
+    special_vars: &[],
+    definition_keywords: &[],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
 
-enum ParseStep {
-    Ahead(usize),
-    Break,
-}
+const HTML_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Html,
+    number: false,
+    hex_number: false,
+    bin_number: false,
+    oct_number: false,
+    number_delim: None,
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[
+        StringKind::new('"').multiline(),
+        StringKind::new('\'').multiline(),
+    ]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: true,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&[]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("<!--"), Cow::Borrowed("-->"))),
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[]),
+    control_statements: Words::Static(&[]),
+    builtin_types: Words::Static(&[]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &[],
+    special_vars: &[],
+    definition_keywords: &[],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: false,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
 
-fn is_sep(c: char) -> bool {
-    c.is_ascii_whitespace() || (c.is_ascii_punctuation() && c != '_') || c == '\0'
-}
+const SHELL_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Shell,
+    number: true,
+    hex_number: false,
+    bin_number: false,
+    oct_number: false,
+    number_delim: None,
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[
+        StringKind::new('"').multiline(),
+        StringKind::new('\'').raw().multiline(),
+        StringKind::new('`').multiline(),
+    ]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: true,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["#"]),
+    statement_comment: None,
+    block_comment: None,
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "alias", "cd", "declare", "echo", "eval", "exec", "exit", "export", "function", "local",
+        "printf", "read", "readonly", "set", "shift", "source", "test", "trap", "unset",
+    ]),
+    control_statements: Words::Static(&[
+        "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done", "case", "esac",
+        "in", "break", "continue", "return",
+    ]),
+    builtin_types: Words::Static(&[]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false"],
+    special_vars: &[],
+    definition_keywords: &["function"],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
 
-struct Highlighter<'a> {
-    syntax: &'a SyntaxHighlight,
-    prev_quote: Option<char>,
-    in_block_comment: bool,
-    prev_hl: Highlight,
-    prev_char: char,
-    num: NumLit,
-    after_def_keyword: bool,
-}
+const SQL_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Sql,
+    number: true,
+    hex_number: false,
+    bin_number: false,
+    oct_number: false,
+    number_delim: None,
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: true,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('\'').raw().multiline()]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["--"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("/*"), Cow::Borrowed("*/"))),
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "ADD",
+        "ALL",
+        "ALTER",
+        "AND",
+        "AS",
+        "ASC",
+        "BETWEEN",
+        "BY",
+        "CASE",
+        "COLUMN",
+        "CONSTRAINT",
+        "CREATE",
+        "DATABASE",
+        "DEFAULT",
+        "DELETE",
+        "DESC",
+        "DISTINCT",
+        "DROP",
+        "ELSE",
+        "END",
+        "EXISTS",
+        "FOREIGN",
+        "FROM",
+        "FULL",
+        "GROUP",
+        "HAVING",
+        "IN",
+        "INDEX",
+        "INNER",
+        "INSERT",
+        "INTO",
+        "IS",
+        "JOIN",
+        "KEY",
+        "LEFT",
+        "LIKE",
+        "LIMIT",
+        "NOT",
+        "OFFSET",
+        "ON",
+        "OR",
+        "ORDER",
+        "OUTER",
+        "PRIMARY",
+        "REFERENCES",
+        "RIGHT",
+        "SELECT",
+        "SET",
+        "TABLE",
+        "THEN",
+        "UNION",
+        "UNIQUE",
+        "UPDATE",
+        "VALUES",
+        "VIEW",
+        "WHEN",
+        "WHERE",
+        "WITH",
+    ]),
+    control_statements: Words::Static(&[]),
+    builtin_types: Words::Static(&[
+        "BIGINT",
+        "BLOB",
+        "BOOLEAN",
+        "CHAR",
+        "DATE",
+        "DECIMAL",
+        "DOUBLE",
+        "FLOAT",
+        "INT",
+        "INTEGER",
+        "NUMERIC",
+        "REAL",
+        "SMALLINT",
+        "TEXT",
+        "TIME",
+        "TIMESTAMP",
+        "VARCHAR",
+    ]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["TRUE", "FALSE"],
+    special_vars: &["NULL"],
+    definition_keywords: &[],
+    label_keywords: &[],
+    case_insensitive_keywords: true,
+    highlight_functions: true,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
 
-impl<'a> Highlighter<'a> {
-    fn new<'b: 'a>(syntax: &'b SyntaxHighlight) -> Self {
-        Self {
-            syntax,
-            prev_quote: None,
-            in_block_comment: false,
-            prev_hl: Highlight::Normal,
-            prev_char: '\0',
-            num: NumLit::Digit,
-            after_def_keyword: false,
-        }
-    }
+const JSON_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Json,
+    number: true,
+    hex_number: false,
+    bin_number: false,
+    oct_number: false,
+    number_delim: None,
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"')]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: true,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&[]),
+    statement_comment: None,
+    block_comment: None,
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&["null"]),
+    control_statements: Words::Static(&[]),
+    builtin_types: Words::Static(&[]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false"],
+    special_vars: &[],
+    definition_keywords: &[],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: false,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
 
-    fn eat_n(
-        &mut self,
+const YAML_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Yaml,
+    number: true,
+    hex_number: false,
+    bin_number: false,
+    oct_number: false,
+    number_delim: None,
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: true,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[
+        StringKind::new('"').multiline(),
+        StringKind::new('\'').raw().multiline(),
+    ]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: Some(&[':']),
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: true,
+    line_comments: Words::Static(&["#"]),
+    statement_comment: None,
+    block_comment: None,
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&["null"]),
+    control_statements: Words::Static(&[]),
+    builtin_types: Words::Static(&[]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false", "yes", "no"],
+    special_vars: &[],
+    definition_keywords: &[],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: false,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const MARKDOWN_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Markdown,
+    number: false,
+    hex_number: false,
+    bin_number: false,
+    oct_number: false,
+    number_delim: None,
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: true,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&[]),
+    statement_comment: None,
+    block_comment: None,
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[]),
+    control_statements: Words::Static(&[]),
+    builtin_types: Words::Static(&[]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &[],
+    special_vars: &[],
+    definition_keywords: &[],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: false,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const PHP_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Php,
+    number: true,
+    hex_number: true,
+    bin_number: true,
+    oct_number: true,
+    number_delim: Some('_'),
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: true,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[
+        StringKind::new('"').multiline(),
+        StringKind::new('\'').raw().multiline(), // No variable is expanded in '...'
+    ]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        // Tags are highlighted as operators since the whole file is highlighted as PHP code
+        "<?php", "<?=", "?>", "<=>", "===", "!==", "**=", "??=", "<<=", ">>=", "?->", "->", "=>",
+        "::", ".=", "==", "!=", "<>", "<=", ">=", "&&", "||", "??", "++", "--", "+=", "-=", "*=",
+        "/=", "%=", "&=", "|=", "^=", "**", "<<", ">>", "+", "-", "*", "/", "%", "<", ">", "=",
+        "!", "&", "|", "^", "~", "?", "@",
+    ],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: true,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["//", "#"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("/*"), Cow::Borrowed("*/"))),
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: Some("/**"),
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "abstract",
+        "as",
+        "class",
+        "clone",
+        "const",
+        "echo",
+        "enum",
+        "extends",
+        "final",
+        "fn",
+        "function",
+        "global",
+        "implements",
+        "include",
+        "include_once",
+        "instanceof",
+        "insteadof",
+        "interface",
+        "namespace",
+        "new",
+        "print",
+        "private",
+        "protected",
+        "public",
+        "readonly",
+        "require",
+        "require_once",
+        "static",
+        "trait",
+        "use",
+        "var",
+    ]),
+    control_statements: Words::Static(&[
+        "break",
+        "case",
+        "catch",
+        "continue",
+        "declare",
+        "default",
+        "do",
+        "else",
+        "elseif",
+        "endfor",
+        "endforeach",
+        "endif",
+        "endswitch",
+        "endwhile",
+        "finally",
+        "for",
+        "foreach",
+        "goto",
+        "if",
+        "match",
+        "return",
+        "switch",
+        "throw",
+        "try",
+        "while",
+        "yield",
+    ]),
+    builtin_types: Words::Static(&[
+        "array", "bool", "callable", "float", "int", "iterable", "mixed", "never", "object",
+        "string", "void",
+    ]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false", "null"],
+    special_vars: &["self", "parent"],
+    definition_keywords: &["function", "class", "interface", "trait", "enum"],
+    label_keywords: &[],
+    case_insensitive_keywords: true,
+    highlight_functions: true,
+    highlight_screaming_case: true,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const LUA_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Lua,
+    number: true,
+    hex_number: true,
+    bin_number: false,
+    oct_number: false,
+    number_delim: None,
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: true,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"'), StringKind::new('\'')]),
+    triple_quotes: &[],
+    line_continuation: true,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: true,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        "...", "..", "==", "~=", "<=", ">=", "//", "<<", ">>", "::", "+", "-", "*", "/", "%", "^",
+        "#", "&", "~", "|", "<", ">", "=",
+    ],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["--"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("--[["), Cow::Borrowed("]]"))),
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&["and", "function", "in", "local", "not", "or"]),
+    control_statements: Words::Static(&[
+        "break", "do", "else", "elseif", "end", "for", "goto", "if", "repeat", "return", "then",
+        "until", "while",
+    ]),
+    builtin_types: Words::Static(&[]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false", "nil"],
+    special_vars: &["self"],
+    definition_keywords: &["function"],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const HASKELL_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Haskell,
+    number: true,
+    hex_number: true,
+    bin_number: true,
+    oct_number: true,
+    number_delim: Some('_'),
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"')]),
+    triple_quotes: &[],
+    line_continuation: true,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: true,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        ">>=", "<$>", "<*>", "::", "->", "<-", "=>", "==", "/=", "<=", ">=", "&&", "||", "++",
+        ">>", "<>", "!!", "$", "\\", "|", "=", "+", "-", "*", "/", "<", ">", "@",
+    ],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["--"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("{-"), Cow::Borrowed("-}"))),
+    nested_block_comment: true,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "as",
+        "case",
+        "class",
+        "data",
+        "deriving",
+        "do",
+        "forall",
+        "hiding",
+        "import",
+        "in",
+        "infix",
+        "infixl",
+        "infixr",
+        "instance",
+        "let",
+        "module",
+        "newtype",
+        "of",
+        "qualified",
+        "type",
+        "where",
+    ]),
+    control_statements: Words::Static(&["if", "then", "else"]),
+    builtin_types: Words::Static(&[]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["True", "False"],
+    special_vars: &[],
+    definition_keywords: &["data", "newtype", "type", "class"],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: false,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: true,
+    macro_bang: false,
+};
+
+const KOTLIN_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Kotlin,
+    number: true,
+    hex_number: true,
+    bin_number: true,
+    oct_number: false,
+    number_delim: Some('_'),
+    number_suffixes: &["uL", "UL", "L", "u", "U", "f", "F"],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: true,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"')]),
+    triple_quotes: &['"'],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: true,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        "===", "!==", "..<", "?.", "?:", "!!", "::", "..", "->", "==", "!=", "<=", ">=", "&&",
+        "||", "++", "--", "+=", "-=", "*=", "/=", "%=", "+", "-", "*", "/", "%", "<", ">", "=",
+        "!", "?",
+    ],
+    decorator: false,
+    annotation_sigil: Some('@'),
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: true,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["//"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("/*"), Cow::Borrowed("*/"))),
+    nested_block_comment: true,
+    doc_line_comments: &[],
+    doc_block_comment: Some("/**"),
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "abstract",
+        "as",
+        "by",
+        "class",
+        "companion",
+        "constructor",
+        "data",
+        "enum",
+        "fun",
+        "import",
+        "in",
+        "init",
+        "inline",
+        "interface",
+        "internal",
+        "is",
+        "lateinit",
+        "object",
+        "open",
+        "out",
+        "override",
+        "package",
+        "private",
+        "protected",
+        "public",
+        "sealed",
+        "suspend",
+        "typealias",
+        "val",
+        "var",
+        "vararg",
+    ]),
+    control_statements: Words::Static(&[
+        "break", "catch", "continue", "do", "else", "finally", "for", "if", "return", "throw",
+        "try", "when", "while",
+    ]),
+    builtin_types: Words::Static(&[
+        "Any", "Array", "Boolean", "Byte", "Char", "Double", "Float", "Int", "List", "Long", "Map",
+        "Nothing", "Set", "Short", "String", "Unit",
+    ]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false", "null"],
+    special_vars: &["this", "super", "it"],
+    definition_keywords: &[
+        "fun",
+        "val",
+        "var",
+        "class",
+        "object",
+        "interface",
+        "typealias",
+    ],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: true,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const SWIFT_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Swift,
+    number: true,
+    hex_number: true,
+    bin_number: true,
+    oct_number: true,
+    number_delim: Some('_'),
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"')]),
+    triple_quotes: &['"'],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        "...", "..<", "===", "!==", "->", "??", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=",
+        "*=", "/=", "%=", "&=", "|=", "^=", "<<", ">>", "+", "-", "*", "/", "%", "<", ">", "=",
+        "!", "&", "|", "^", "~", "?",
+    ],
+    decorator: true,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: true,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: true,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["//"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("/*"), Cow::Borrowed("*/"))),
+    nested_block_comment: true,
+    doc_line_comments: &["///"],
+    doc_block_comment: Some("/**"),
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "as",
+        "associatedtype",
+        "async",
+        "await",
+        "class",
+        "convenience",
+        "deinit",
+        "enum",
+        "extension",
+        "fileprivate",
+        "final",
+        "func",
+        "import",
+        "init",
+        "inout",
+        "internal",
+        "is",
+        "lazy",
+        "let",
+        "mutating",
+        "open",
+        "operator",
+        "override",
+        "private",
+        "protocol",
+        "public",
+        "required",
+        "rethrows",
+        "some",
+        "static",
+        "struct",
+        "subscript",
+        "throws",
+        "typealias",
+        "var",
+        "weak",
+        "where",
+    ]),
+    control_statements: Words::Static(&[
+        "break",
+        "case",
+        "catch",
+        "continue",
+        "default",
+        "defer",
+        "do",
+        "else",
+        "fallthrough",
+        "for",
+        "guard",
+        "if",
+        "in",
+        "repeat",
+        "return",
+        "switch",
+        "throw",
+        "try",
+        "while",
+    ]),
+    builtin_types: Words::Static(&[
+        "Any",
+        "AnyObject",
+        "Array",
+        "Bool",
+        "Character",
+        "Dictionary",
+        "Double",
+        "Float",
+        "Int",
+        "Int8",
+        "Int16",
+        "Int32",
+        "Int64",
+        "Optional",
+        "Set",
+        "String",
+        "UInt",
+        "UInt8",
+        "UInt16",
+        "UInt32",
+        "UInt64",
+        "Void",
+    ]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false", "nil"],
+    special_vars: &["self", "Self", "super"],
+    definition_keywords: &[
+        "func",
+        "let",
+        "var",
+        "class",
+        "struct",
+        "enum",
+        "protocol",
+        "typealias",
+    ],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const JAVA_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Java,
+    number: true,
+    hex_number: true,
+    bin_number: true,
+    oct_number: false,
+    number_delim: Some('_'),
+    number_suffixes: &["L", "l", "F", "f", "D", "d"],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: true,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"')]),
+    triple_quotes: &['"'], // Text block
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: true,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        ">>>=", "<<=", ">>=", ">>>", "::", "->", "++", "--", "<<", ">>", "<=", ">=", "==", "!=",
+        "&&", "||", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "+", "-", "*", "/", "%", "<",
+        ">", "=", "!", "&", "|", "^", "~", "?", ":",
+    ],
+    decorator: false,
+    annotation_sigil: Some('@'),
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["//"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("/*"), Cow::Borrowed("*/"))),
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: Some("/**"),
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "abstract",
+        "assert",
+        "class",
+        "const",
+        "enum",
+        "extends",
+        "final",
+        "implements",
+        "import",
+        "instanceof",
+        "interface",
+        "native",
+        "new",
+        "package",
+        "permits",
+        "private",
+        "protected",
+        "public",
+        "record",
+        "sealed",
+        "static",
+        "strictfp",
+        "synchronized",
+        "throws",
+        "transient",
+        "var",
+        "volatile",
+    ]),
+    control_statements: Words::Static(&[
+        "break", "case", "catch", "continue", "default", "do", "else", "finally", "for", "if",
+        "return", "switch", "throw", "try", "while", "yield",
+    ]),
+    builtin_types: Words::Static(&[
+        "boolean",
+        "byte",
+        "char",
+        "double",
+        "float",
+        "int",
+        "long",
+        "short",
+        "void",
+        "Boolean",
+        "Byte",
+        "Character",
+        "Double",
+        "Float",
+        "Integer",
+        "Long",
+        "Object",
+        "Short",
+        "String",
+    ]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false", "null"],
+    special_vars: &["this", "super"],
+    definition_keywords: &["class", "interface", "enum", "record"],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: true,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const TOML_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Toml,
+    number: true,
+    hex_number: true,
+    bin_number: true,
+    oct_number: true,
+    number_delim: Some('_'),
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: true,
+    string_quotes: Cow::Borrowed(&[
+        StringKind::new('"'),
+        StringKind::new('\'').raw(), // Literal string
+    ]),
+    triple_quotes: &['"', '\''],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: Some(&['=']),
+    table_headers: true,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["#"]),
+    statement_comment: None,
+    block_comment: None,
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[]),
+    control_statements: Words::Static(&[]),
+    builtin_types: Words::Static(&[]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false", "inf", "nan"],
+    special_vars: &[],
+    definition_keywords: &[],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: false,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const ELIXIR_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Elixir,
+    number: true,
+    hex_number: true,
+    bin_number: true,
+    oct_number: true,
+    number_delim: Some('_'),
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[
+        StringKind::new('"').multiline(),
+        StringKind::new('\'').multiline(),
+    ]),
+    triple_quotes: &['"', '\''],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: true,
+    regex_literal: false,
+    sigils: true,
+    quote_operators: &[],
+    operators: &[
+        "|>", "->", "<-", "=>", "++", "--", "<>", "===", "!==", "==", "!=", "<=", ">=", "&&", "||",
+        "=~", "..", "::", "\\", "+", "-", "*", "/", "=", "<", ">", "!", "|", "&", "^",
+    ],
+    decorator: true,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: true,
+    anchors: false,
+    line_comments: Words::Static(&["#"]),
+    statement_comment: None,
+    block_comment: None,
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "alias",
+        "and",
+        "def",
+        "defdelegate",
+        "defexception",
+        "defguard",
+        "defimpl",
+        "defmacro",
+        "defmacrop",
+        "defmodule",
+        "defoverridable",
+        "defp",
+        "defprotocol",
+        "defstruct",
+        "do",
+        "end",
+        "fn",
+        "import",
+        "in",
+        "not",
+        "or",
+        "quote",
+        "require",
+        "unquote",
+        "use",
+        "when",
+    ]),
+    control_statements: Words::Static(&[
+        "after", "case", "catch", "cond", "else", "for", "if", "raise", "receive", "rescue",
+        "throw", "try", "unless", "with",
+    ]),
+    builtin_types: Words::Static(&[]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false"],
+    special_vars: &[
+        "nil",
+        "__MODULE__",
+        "__DIR__",
+        "__ENV__",
+        "__CALLER__",
+        "__STACKTRACE__",
+    ],
+    definition_keywords: &["def", "defp", "defmacro", "defmacrop", "defguard"],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: true,
+    macro_bang: false,
+};
+
+const OCAML_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::OCaml,
+    number: true,
+    hex_number: true,
+    bin_number: true,
+    oct_number: true,
+    number_delim: Some('_'),
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"').multiline()]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: true,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        "|>", "@@", "::", ":=", "->", "<-", "==", "!=", "<>", "<=", ">=", "&&", "||", "+.", "-.",
+        "*.", "/.", "|", "=", "+", "-", "*", "/", "<", ">", "@", "^", "!",
+    ],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&[]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("(*"), Cow::Borrowed("*)"))),
+    nested_block_comment: true,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "and",
+        "as",
+        "begin",
+        "class",
+        "constraint",
+        "end",
+        "exception",
+        "external",
+        "fun",
+        "function",
+        "functor",
+        "in",
+        "include",
+        "inherit",
+        "initializer",
+        "lazy",
+        "let",
+        "method",
+        "module",
+        "mutable",
+        "new",
+        "object",
+        "of",
+        "open",
+        "private",
+        "rec",
+        "sig",
+        "struct",
+        "type",
+        "val",
+        "virtual",
+    ]),
+    control_statements: Words::Static(&[
+        "assert", "do", "done", "downto", "else", "for", "if", "match", "raise", "then", "to",
+        "try", "when", "while", "with",
+    ]),
+    builtin_types: Words::Static(&[
+        "array", "bool", "bytes", "char", "exn", "float", "int", "list", "option", "string", "unit",
+    ]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false"],
+    special_vars: &[],
+    definition_keywords: &["let", "type", "module", "and"],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: false,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: true,
+    macro_bang: false,
+};
+
+const NIM_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Nim,
+    number: true,
+    hex_number: true,
+    bin_number: true,
+    oct_number: true,
+    number_delim: Some('_'),
+    number_suffixes: &[
+        "'i", "'i8", "'i16", "'i32", "'i64", "'u", "'u8", "'u16", "'u32", "'u64", "'f", "'f32",
+        "'f64", "i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64", "f32", "f64",
+    ],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"')]),
+    triple_quotes: &['"'],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: true,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: true,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        "..<", "==", "!=", "<=", ">=", "..", "=>", "->", "+=", "-=", "*=", "/=", "&=", "=", "+",
+        "-", "*", "/", "<", ">", "&", "@", "$", "^", "%", "~", "|",
+    ],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["#"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("#["), Cow::Borrowed("]#"))),
+    nested_block_comment: true,
+    doc_line_comments: &["##"],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "addr",
+        "and",
+        "as",
+        "asm",
+        "bind",
+        "cast",
+        "concept",
+        "const",
+        "converter",
+        "distinct",
+        "div",
+        "do",
+        "end",
+        "enum",
+        "export",
+        "from",
+        "func",
+        "import",
+        "in",
+        "include",
+        "interface",
+        "is",
+        "isnot",
+        "iterator",
+        "let",
+        "macro",
+        "method",
+        "mixin",
+        "mod",
+        "not",
+        "notin",
+        "object",
+        "or",
+        "out",
+        "proc",
+        "ptr",
+        "ref",
+        "shl",
+        "shr",
+        "static",
+        "template",
+        "tuple",
+        "type",
+        "using",
+        "var",
+        "xor",
+    ]),
+    control_statements: Words::Static(&[
+        "block", "break", "case", "continue", "defer", "discard", "elif", "else", "except",
+        "finally", "for", "if", "of", "raise", "return", "try", "when", "while", "yield",
+    ]),
+    builtin_types: Words::Static(&[
+        "array",
+        "auto",
+        "bool",
+        "byte",
+        "char",
+        "cstring",
+        "float",
+        "float32",
+        "float64",
+        "int",
+        "int8",
+        "int16",
+        "int32",
+        "int64",
+        "openArray",
+        "pointer",
+        "seq",
+        "set",
+        "string",
+        "typed",
+        "uint",
+        "uint8",
+        "uint16",
+        "uint32",
+        "uint64",
+        "untyped",
+        "varargs",
+        "void",
+    ]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false"],
+    special_vars: &["nil", "result"],
+    definition_keywords: &[
+        "proc",
+        "func",
+        "method",
+        "iterator",
+        "template",
+        "macro",
+        "converter",
+    ],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: true,
+    macro_bang: false,
+};
+
+const ZIG_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Zig,
+    number: true,
+    hex_number: true,
+    bin_number: true,
+    oct_number: true,
+    number_delim: Some('_'),
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: true,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"')]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: Some("\\\\"), // Multiline string literal such as \\foo
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: true,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        "<<|=", "+%=", "-%=", "*%=", "+|=", "-|=", "*|=", "<<=", ">>=", "<<|", "==", "!=", "<=",
+        ">=", "=>", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "+%", "-%", "*%", "+|", "-|",
+        "*|", "++", "**", "||", "<<", ">>", ".?", ".*", "=", "+", "-", "*", "/", "%", "<", ">",
+        "&", "|", "^", "~", "!",
+    ],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: true,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["//"]),
+    statement_comment: None,
+    block_comment: None,
+    nested_block_comment: false,
+    doc_line_comments: &["///", "//!"],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "addrspace",
+        "align",
+        "allowzero",
+        "and",
+        "anyframe",
+        "asm",
+        "async",
+        "await",
+        "callconv",
+        "comptime",
+        "const",
+        "enum",
+        "error",
+        "export",
+        "extern",
+        "fn",
+        "inline",
+        "linksection",
+        "noalias",
+        "noinline",
+        "nosuspend",
+        "opaque",
+        "or",
+        "orelse",
+        "packed",
+        "pub",
+        "resume",
+        "struct",
+        "suspend",
+        "test",
+        "threadlocal",
+        "union",
+        "usingnamespace",
+        "var",
+        "volatile",
+    ]),
+    control_statements: Words::Static(&[
+        "break",
+        "catch",
+        "continue",
+        "defer",
+        "else",
+        "errdefer",
+        "for",
+        "if",
+        "return",
+        "switch",
+        "try",
+        "unreachable",
+        "while",
+    ]),
+    builtin_types: Words::Static(&[
+        "anyerror",
+        "anyopaque",
+        "anytype",
+        "bool",
+        "c_char",
+        "c_int",
+        "c_long",
+        "c_short",
+        "c_uint",
+        "c_ulong",
+        "comptime_float",
+        "comptime_int",
+        "f16",
+        "f32",
+        "f64",
+        "f80",
+        "f128",
+        "i8",
+        "i16",
+        "i32",
+        "i64",
+        "i128",
+        "isize",
+        "noreturn",
+        "type",
+        "u8",
+        "u16",
+        "u32",
+        "u64",
+        "u128",
+        "usize",
+        "void",
+    ]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false"],
+    special_vars: &["null", "undefined"],
+    definition_keywords: &["fn", "const", "var"],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const SCALA_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Scala,
+    number: true,
+    hex_number: true,
+    bin_number: false,
+    oct_number: false,
+    number_delim: Some('_'),
+    number_suffixes: &["L", "l", "f", "F", "d", "D"],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: true,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"')]),
+    triple_quotes: &['"'],
+    line_continuation: false,
+    string_prefixes: &["s", "f", "raw"],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: true,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        "<-", "->", "=>", "<:", ">:", "::", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=", "*=",
+        "/=", "++", "+", "-", "*", "/", "%", "<", ">", "=", "!", "&", "|", "^",
+    ],
+    decorator: false,
+    annotation_sigil: Some('@'),
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: true,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["//"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("/*"), Cow::Borrowed("*/"))),
+    nested_block_comment: true,
+    doc_line_comments: &[],
+    doc_block_comment: Some("/**"),
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "abstract",
+        "class",
+        "def",
+        "derives",
+        "enum",
+        "export",
+        "extends",
+        "extension",
+        "final",
+        "given",
+        "implicit",
+        "import",
+        "lazy",
+        "new",
+        "object",
+        "opaque",
+        "override",
+        "package",
+        "private",
+        "protected",
+        "sealed",
+        "trait",
+        "type",
+        "using",
+        "val",
+        "var",
+        "with",
+    ]),
+    control_statements: Words::Static(&[
+        "case", "catch", "do", "else", "end", "finally", "for", "if", "match", "return", "then",
+        "throw", "try", "while", "yield",
+    ]),
+    builtin_types: Words::Static(&[
+        "Any", "AnyRef", "AnyVal", "Array", "Boolean", "Byte", "Char", "Double", "Float", "Int",
+        "List", "Long", "Map", "Nothing", "Null", "Option", "Seq", "Set", "Short", "String",
+        "Unit", "Vector",
+    ]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false"],
+    special_vars: &["null", "this", "super", "None"],
+    definition_keywords: &[
+        "def", "val", "var", "class", "object", "trait", "type", "enum", "given",
+    ],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const MAKEFILE_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Makefile,
+    number: true,
+    hex_number: false,
+    bin_number: false,
+    oct_number: false,
+    number_delim: None,
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[
+        StringKind::new('"'),
+        StringKind::new('\'').raw(),
+        StringKind::new('`').multiline(),
+    ]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: true,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: true,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["#"]),
+    statement_comment: None,
+    block_comment: None,
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "-include", "define", "else", "endef", "endif", "export", "ifdef", "ifeq", "ifndef",
+        "ifneq", "include", "override", "private", "sinclude", "undefine", "unexport", "vpath",
+    ]),
+    control_statements: Words::Static(&[
+        "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done", "case", "esac",
+        "in", "break", "continue", "return",
+    ]),
+    builtin_types: Words::Static(&[]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false"],
+    special_vars: &[],
+    definition_keywords: &[],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: false,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const PERL_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Perl,
+    number: true,
+    hex_number: true,
+    bin_number: true,
+    oct_number: true,
+    number_delim: Some('_'),
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[
+        StringKind::new('"').multiline(),
+        StringKind::new('\'').multiline(),
+        StringKind::new('`').multiline(),
+    ]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &["q", "qq", "qw", "qx"],
+    operators: &[],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: true,
+    variable_scopes: &[],
+    sigil_variables: true,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["#"]),
+    statement_comment: None,
+    block_comment: None,
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: Some(("=pod", "=cut")),
+    keywords: Words::Static(&[
+        "BEGIN", "END", "and", "cmp", "eq", "ge", "gt", "le", "local", "lt", "my", "ne", "no",
+        "not", "or", "our", "package", "require", "state", "sub", "use", "x", "xor",
+    ]),
+    control_statements: Words::Static(&[
+        "do", "else", "elsif", "for", "foreach", "if", "last", "next", "redo", "return", "unless",
+        "until", "while",
+    ]),
+    builtin_types: Words::Static(&[]),
+    builtin_functions: Words::Static(&[
+        "bless",
+        "chomp",
+        "chop",
+        "close",
+        "defined",
+        "delete",
+        "die",
+        "each",
+        "eval",
+        "exists",
+        "grep",
+        "join",
+        "keys",
+        "lc",
+        "length",
+        "map",
+        "open",
+        "pop",
+        "print",
+        "printf",
+        "push",
+        "ref",
+        "reverse",
+        "say",
+        "scalar",
+        "shift",
+        "sort",
+        "splice",
+        "split",
+        "sprintf",
+        "substr",
+        "uc",
+        "undef",
+        "unshift",
+        "values",
+        "wantarray",
+        "warn",
+    ]),
+    boolean_constants: &[],
+    special_vars: &["__PACKAGE__", "__FILE__", "__LINE__", "__DATA__", "__END__"],
+    definition_keywords: &["sub", "package"],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const R_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::R,
+    number: true,
+    hex_number: true,
+    bin_number: false,
+    oct_number: false,
+    number_delim: None,
+    number_suffixes: &["L", "i"],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[
+        StringKind::new('"').multiline(),
+        StringKind::new('\'').multiline(),
+        StringKind::new('`').multiline(),
+    ]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        "%in%", "%/%", "%*%", "%o%", "%>%", "<<-", "->>", "%%", "<-", "->", "|>", "==", "!=", "<=",
+        ">=", "&&", "||", "::", "+", "-", "*", "/", "^", "<", ">", "=", "!", "&", "|", "~", "$",
+        "@", ":", "?",
+    ],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["#"]),
+    statement_comment: None,
+    block_comment: None,
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&["function"]),
+    control_statements: Words::Static(&[
+        "if", "else", "for", "while", "repeat", "in", "break", "next", "return",
+    ]),
+    builtin_types: Words::Static(&[]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["TRUE", "FALSE", "T", "F"],
+    special_vars: &[
+        "NULL",
+        "NA",
+        "NA_integer_",
+        "NA_real_",
+        "NA_character_",
+        "NaN",
+        "Inf",
+    ],
+    definition_keywords: &[],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const JULIA_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Julia,
+    number: true,
+    hex_number: true,
+    bin_number: true,
+    oct_number: true,
+    number_delim: Some('_'),
+    number_suffixes: &["im"],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[
+        StringKind::new('"').multiline(),
+        StringKind::new('`').multiline(),
+    ]),
+    triple_quotes: &['"', '`'],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: true,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: true,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: true,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["#"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("#="), Cow::Borrowed("=#"))),
+    nested_block_comment: true,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "abstract",
+        "baremodule",
+        "begin",
+        "const",
+        "do",
+        "end",
+        "export",
+        "function",
+        "global",
+        "import",
+        "let",
+        "local",
+        "macro",
+        "module",
+        "mutable",
+        "primitive",
+        "quote",
+        "struct",
+        "type",
+        "using",
+        "where",
+    ]),
+    control_statements: Words::Static(&[
+        "break", "catch", "continue", "else", "elseif", "finally", "for", "if", "in", "isa",
+        "return", "try", "while",
+    ]),
+    builtin_types: Words::Static(&[
+        "Any", "Bool", "Char", "Dict", "Float32", "Float64", "Int", "Int8", "Int16", "Int32",
+        "Int64", "Nothing", "String", "Symbol", "UInt", "UInt8", "UInt16", "UInt32", "UInt64",
+        "Vector",
+    ]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false"],
+    special_vars: &["nothing", "missing"],
+    definition_keywords: &["function", "struct", "module", "macro"],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const GRAPHQL_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::GraphQl,
+    number: true,
+    hex_number: false,
+    bin_number: false,
+    oct_number: false,
+    number_delim: None,
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"')]),
+    triple_quotes: &['"'],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[],
+    decorator: true,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: true,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: true,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["#"]),
+    statement_comment: None,
+    block_comment: None,
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "directive",
+        "enum",
+        "extend",
+        "fragment",
+        "implements",
+        "input",
+        "interface",
+        "mutation",
+        "on",
+        "query",
+        "repeatable",
+        "scalar",
+        "schema",
+        "subscription",
+        "type",
+        "union",
+    ]),
+    control_statements: Words::Static(&[]),
+    builtin_types: Words::Static(&["ID", "String", "Int", "Float", "Boolean"]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false"],
+    special_vars: &["null"],
+    definition_keywords: &[
+        "enum",
+        "fragment",
+        "input",
+        "interface",
+        "mutation",
+        "query",
+        "scalar",
+        "subscription",
+        "type",
+        "union",
+    ],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: false,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: true,
+    macro_bang: false,
+};
+
+const PROTO_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Proto,
+    number: true,
+    hex_number: true,
+    bin_number: false,
+    oct_number: true,
+    number_delim: None,
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"'), StringKind::new('\'')]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["//"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("/*"), Cow::Borrowed("*/"))),
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "enum",
+        "extend",
+        "extensions",
+        "import",
+        "map",
+        "message",
+        "oneof",
+        "option",
+        "optional",
+        "package",
+        "public",
+        "repeated",
+        "required",
+        "reserved",
+        "returns",
+        "rpc",
+        "service",
+        "stream",
+        "syntax",
+        "to",
+        "weak",
+    ]),
+    control_statements: Words::Static(&[]),
+    builtin_types: Words::Static(&[
+        "double", "float", "int32", "int64", "uint32", "uint64", "sint32", "sint64", "fixed32",
+        "fixed64", "sfixed32", "sfixed64", "bool", "string", "bytes",
+    ]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false"],
+    special_vars: &["max"],
+    definition_keywords: &["message", "enum", "service", "rpc", "oneof", "extend"],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: false,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const CSHARP_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::CSharp,
+    number: true,
+    hex_number: true,
+    bin_number: true,
+    oct_number: false,
+    number_delim: Some('_'),
+    number_suffixes: &[
+        "L", "l", "U", "u", "UL", "ul", "LU", "lu", "F", "f", "D", "d", "M", "m",
+    ],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: true,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"')]),
+    triple_quotes: &['"'], // Raw string literal,
+    line_continuation: false,
+    string_prefixes: &["$"],
+    interpolated_prefixes: &["$"],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: true,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: true,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        "<<=", ">>=", "??=", "=>", "++", "--", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||",
+        "??", "?.", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "+", "-", "*", "/", "%", "<",
+        ">", "=", "!", "&", "|", "^", "~", "?", ":",
+    ],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: true,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&["//"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("/*"), Cow::Borrowed("*/"))),
+    nested_block_comment: false,
+    doc_line_comments: &["///"],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "abstract",
+        "as",
+        "async",
+        "await",
+        "base",
+        "checked",
+        "class",
+        "const",
+        "delegate",
+        "enum",
+        "event",
+        "explicit",
+        "extern",
+        "fixed",
+        "get",
+        "implicit",
+        "init",
+        "interface",
+        "internal",
+        "is",
+        "lock",
+        "namespace",
+        "new",
+        "operator",
+        "out",
+        "override",
+        "params",
+        "partial",
+        "private",
+        "protected",
+        "public",
+        "readonly",
+        "record",
+        "ref",
+        "sealed",
+        "set",
+        "sizeof",
+        "stackalloc",
+        "static",
+        "struct",
+        "typeof",
+        "unchecked",
+        "unsafe",
+        "using",
+        "var",
+        "virtual",
+        "volatile",
+        "where",
+    ]),
+    control_statements: Words::Static(&[
+        "break", "case", "catch", "continue", "default", "do", "else", "finally", "for", "foreach",
+        "goto", "if", "in", "return", "switch", "throw", "try", "when", "while", "yield",
+    ]),
+    builtin_types: Words::Static(&[
+        "bool", "byte", "char", "decimal", "double", "dynamic", "float", "int", "long", "nint",
+        "nuint", "object", "sbyte", "short", "string", "uint", "ulong", "ushort", "void",
+    ]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false"],
+    special_vars: &["null", "this", "value"],
+    definition_keywords: &[
+        "class",
+        "struct",
+        "interface",
+        "enum",
+        "record",
+        "namespace",
+    ],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const INI_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Ini,
+    number: true,
+    hex_number: false,
+    bin_number: false,
+    oct_number: false,
+    number_delim: None,
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"'), StringKind::new('\'')]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: Some(&['=', ':']),
+    table_headers: true,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&[";", "#"]),
+    statement_comment: None,
+    block_comment: None,
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[]),
+    control_statements: Words::Static(&[]),
+    builtin_types: Words::Static(&[]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &["true", "false", "yes", "no", "on", "off"],
+    special_vars: &[],
+    definition_keywords: &[],
+    label_keywords: &[],
+    case_insensitive_keywords: true,
+    highlight_functions: false,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const VIM_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::VimScript,
+    number: true,
+    hex_number: true,
+    bin_number: true,
+    oct_number: false,
+    number_delim: None,
+    number_suffixes: &[],
+    immediate_prefixes: &[],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[StringKind::new('"'), StringKind::new('\'').raw()]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[
+        "==#", "==?", "!=#", "!=?", "=~#", "=~?", "..=", "=~", "!~", "..", "||", "&&", "==", "!=",
+        ">=", "<=", "+=", "-=", ".=", "+", "-", "*", "/", "%", "<", ">", "=", "!", "?",
+    ],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: false,
+    statement_labels: false,
+    dot_directives: false,
+    dollar_variables: false,
+    variable_scopes: &["g:", "s:", "l:", "b:", "w:", "t:", "a:", "v:"],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&[]),
+    statement_comment: Some("\""),
+    block_comment: None,
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "augroup",
+        "autocmd",
+        "call",
+        "command",
+        "echo",
+        "echom",
+        "echomsg",
+        "execute",
+        "function",
+        "highlight",
+        "let",
+        "map",
+        "nnoremap",
+        "noremap",
+        "normal",
+        "set",
+        "setlocal",
+        "source",
+        "syntax",
+        "unlet",
+        "vnoremap",
+        "inoremap",
+    ]),
+    control_statements: Words::Static(&[
+        "break",
+        "catch",
+        "continue",
+        "else",
+        "elseif",
+        "endfor",
+        "endfunction",
+        "endif",
+        "endtry",
+        "endwhile",
+        "finally",
+        "for",
+        "if",
+        "in",
+        "return",
+        "throw",
+        "try",
+        "while",
+    ]),
+    builtin_types: Words::Static(&[]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &[],
+    special_vars: &[],
+    definition_keywords: &["function"],
+    label_keywords: &[],
+    case_insensitive_keywords: false,
+    highlight_functions: true,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+const ASM_SYNTAX: SyntaxHighlight = SyntaxHighlight {
+    lang: Language::Asm,
+    number: true,
+    hex_number: true,
+    bin_number: true,
+    oct_number: false,
+    number_delim: None,
+    number_suffixes: &[],
+    immediate_prefixes: &['$', '#'],
+    hex_float: false,
+    leading_decimal_point: false,
+    datetime: false,
+    string_quotes: Cow::Borrowed(&[
+        StringKind::new('"'),
+        StringKind::new('\''),
+        StringKind::new('`').multiline(),
+    ]),
+    triple_quotes: &[],
+    line_continuation: false,
+    string_prefixes: &[],
+    interpolated_prefixes: &[],
+    raw_string: false,
+    line_strings: None,
+    long_brackets: false,
+    delimited_raw_string: false,
+    verbatim_strings: false,
+    object_keys: false,
+    mapping_keys: None,
+    table_headers: false,
+    character: false,
+    lifetime: false,
+    symbol: false,
+    regex_literal: false,
+    sigils: false,
+    quote_operators: &[],
+    operators: &[],
+    decorator: false,
+    annotation_sigil: None,
+    at_builtins: false,
+    at_macros: false,
+    attribute: false,
+    preprocessor: false,
+    hash_directives: false,
+    hex_color: false,
+    property_names: false,
+    field_names: false,
+    markup: false,
+    markdown: false,
+    makefile: false,
+    labels: true,
+    statement_labels: false,
+    dot_directives: true,
+    dollar_variables: false,
+    variable_scopes: &[],
+    sigil_variables: false,
+    paren_interpolation: false,
+    hash_interpolation: false,
+    anchors: false,
+    line_comments: Words::Static(&[";", "# ", "//"]),
+    statement_comment: None,
+    block_comment: Some((Cow::Borrowed("/*"), Cow::Borrowed("*/"))),
+    nested_block_comment: false,
+    doc_line_comments: &[],
+    doc_block_comment: None,
+    line_head_block_comment: None,
+    keywords: Words::Static(&[
+        "bits", "db", "dd", "dq", "dw", "equ", "extern", "global", "incbin", "resb", "resd",
+        "resq", "resw", "section", "segment", "times", "byte", "word", "dword", "qword", "ptr",
+    ]),
+    control_statements: Words::Static(&[
+        "call", "ret", "jmp", "je", "jne", "jz", "jnz", "jg", "jge", "jl", "jle", "ja", "jae",
+        "jb", "jbe", "loop", "syscall", "int",
+    ]),
+    builtin_types: Words::Static(&[
+        "rax", "eax", "ax", "rbx", "ebx", "bx", "rcx", "ecx", "cx", "rdx", "edx", "dx", "rsi",
+        "esi", "si", "rdi", "edi", "di", "rbp", "ebp", "bp", "rsp", "esp", "sp", "al", "ah", "bl",
+        "bh", "cl", "ch", "dl", "dh", "sil", "dil", "bpl", "spl", "r8", "r8d", "r8w", "r8b", "r9",
+        "r9d", "r9w", "r9b", "r10", "r10d", "r10w", "r10b", "r11", "r11d", "r11w", "r11b", "r12",
+        "r12d", "r12w", "r12b", "r13", "r13d", "r13w", "r13b", "r14", "r14d", "r14w", "r14b",
+        "r15", "r15d", "r15w", "r15b", "rip", "eip", "cs", "ds", "es", "fs", "gs", "ss", "rflags",
+        "eflags", "xmm0", "xmm1", "xmm2", "xmm3", "xmm4", "xmm5", "xmm6", "xmm7", "xmm8", "xmm9",
+        "xmm10", "xmm11", "xmm12", "xmm13", "xmm14", "xmm15", "ymm0", "ymm1", "ymm2", "ymm3",
+        "ymm4", "ymm5", "ymm6", "ymm7", "ymm8", "ymm9", "ymm10", "ymm11", "ymm12", "ymm13",
+        "ymm14", "ymm15",
+    ]),
+    builtin_functions: Words::Static(&[]),
+    boolean_constants: &[],
+    special_vars: &[],
+    definition_keywords: &[],
+    label_keywords: &[],
+    case_insensitive_keywords: true,
+    highlight_functions: false,
+    highlight_screaming_case: false,
+    highlight_capitalized_types: false,
+    macro_bang: false,
+};
+
+impl SyntaxHighlight {
+    fn for_lang(lang: Language) -> Arc<SyntaxHighlight> {
+        use Language::*;
+        let syntax = match lang {
+            Plain => PLAIN_SYNTAX,
+            C => C_SYNTAX,
+            Rust => RUST_SYNTAX,
+            JavaScript => JAVASCRIPT_SYNTAX,
+            TypeScript => TYPESCRIPT_SYNTAX,
+            Go => GO_SYNTAX,
+            Cpp => CPP_SYNTAX,
+            Python => PYTHON_SYNTAX,
+            Ruby => RUBY_SYNTAX,
+            Css => CSS_SYNTAX,
+            Html => HTML_SYNTAX,
+            Shell => SHELL_SYNTAX,
+            Sql => SQL_SYNTAX,
+            Json => JSON_SYNTAX,
+            Yaml => YAML_SYNTAX,
+            Markdown => MARKDOWN_SYNTAX,
+            Php => PHP_SYNTAX,
+            Lua => LUA_SYNTAX,
+            Haskell => HASKELL_SYNTAX,
+            Kotlin => KOTLIN_SYNTAX,
+            Swift => SWIFT_SYNTAX,
+            Java => JAVA_SYNTAX,
+            Toml => TOML_SYNTAX,
+            Elixir => ELIXIR_SYNTAX,
+            OCaml => OCAML_SYNTAX,
+            Nim => NIM_SYNTAX,
+            Zig => ZIG_SYNTAX,
+            Scala => SCALA_SYNTAX,
+            Makefile => MAKEFILE_SYNTAX,
+            Perl => PERL_SYNTAX,
+            R => R_SYNTAX,
+            Julia => JULIA_SYNTAX,
+            GraphQl => GRAPHQL_SYNTAX,
+            Proto => PROTO_SYNTAX,
+            CSharp => CSHARP_SYNTAX,
+            Ini => INI_SYNTAX,
+            VimScript => VIM_SYNTAX,
+            Asm => ASM_SYNTAX,
+            Custom(name) => match syntax_file::find_syntax(name) {
+                Some(syntax) => return syntax,
+                None => PLAIN_SYNTAX,
+            },
+        };
+        Arc::new(syntax)
+    }
+
+    pub fn lang(&self) -> Language {
+        self.lang
+    }
+
+    fn string_kind(&self, quote: char) -> Option<&StringKind> {
+        self.string_quotes.iter().find(|k| k.quote == quote)
+    }
+
+    // Build syntax from key-value pairs in syntax definition file
+    pub fn from_definition(name: &str, pairs: Vec<(usize, String, Value)>) -> Result<Self> {
+        let mut syntax = PLAIN_SYNTAX;
+        let mut quotes = vec![];
+        let mut multiline = false;
+
+        for (line, key, value) in pairs {
+            let error = |msg: &str| Err(Error::InvalidSyntaxFile(line, msg.to_string()));
+            match (key.as_str(), value) {
+                ("line_comment", Value::Str(s)) => syntax.line_comments = Words::Owned(vec![s]),
+                ("line_comment", Value::Array(a)) => syntax.line_comments = Words::Owned(a),
+                ("block_comment", Value::Array(mut a)) if a.len() == 2 => {
+                    let end = a.pop().unwrap();
+                    let start = a.pop().unwrap();
+                    syntax.block_comment = Some((Cow::Owned(start), Cow::Owned(end)));
+                }
+                ("block_comment", _) => return error("'block_comment' must be [start, end]"),
+                ("keywords", Value::Array(a)) => syntax.keywords = Words::Owned(a),
+                ("control_statements", Value::Array(a)) => {
+                    syntax.control_statements = Words::Owned(a)
+                }
+                ("builtin_types", Value::Array(a)) => syntax.builtin_types = Words::Owned(a),
+                ("builtin_functions", Value::Array(a)) => {
+                    syntax.builtin_functions = Words::Owned(a)
+                }
+                ("string_quotes", Value::Array(a)) => {
+                    quotes.clear();
+                    for s in a.iter() {
+                        let mut chars = s.chars();
+                        match (chars.next(), chars.next()) {
+                            (Some(c), None) => quotes.push(StringKind::new(c)),
+                            _ => {
+                                return error("each quote in 'string_quotes' must be one character")
+                            }
+                        }
+                    }
+                }
+                ("multiline_strings", Value::Bool(b)) => multiline = b,
+                ("number", Value::Bool(b)) => syntax.number = b,
+                ("character", Value::Bool(b)) => syntax.character = b,
+                (
+                    "line_comment" | "keywords" | "control_statements" | "builtin_types"
+                    | "builtin_functions" | "string_quotes" | "multiline_strings" | "number"
+                    | "character",
+                    v,
+                ) => {
+                    let msg = format!("unexpected {} value for '{}'", v.type_name(), key);
+                    return error(&msg);
+                }
+                _ => return error(&format!("unknown key '{}'", key)),
+            }
+        }
+
+        if multiline {
+            quotes = quotes.into_iter().map(StringKind::multiline).collect();
+        }
+        syntax.string_quotes = Cow::Owned(quotes);
+        // Intern the name only after validation so that an invalid definition leaks nothing
+        syntax.lang = Language::Custom(syntax_file::intern_name(name));
+        Ok(syntax)
+    }
+}
+
+// Build syntax of custom language in code. Built syntax is registered with
+// `Highlighting::register_syntax`.
+//
+//   let syntax = SyntaxHighlightBuilder::new("toy").line_comment("//").keyword("let").build();
+//   let lang = Highlighting::register_syntax(syntax, &["toy"]);
+pub struct SyntaxHighlightBuilder {
+    name: String,
+    line_comments: Vec<String>,
+    block_comment: Option<(String, String)>,
+    keywords: Vec<String>,
+    control_statements: Vec<String>,
+    builtin_types: Vec<String>,
+    builtin_functions: Vec<String>,
+    string_quotes: Vec<StringKind>,
+    multiline_strings: bool,
+    number: bool,
+    character: bool,
+}
+
+impl SyntaxHighlightBuilder {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            line_comments: vec![],
+            block_comment: None,
+            keywords: vec![],
+            control_statements: vec![],
+            builtin_types: vec![],
+            builtin_functions: vec![],
+            string_quotes: vec![],
+            multiline_strings: false,
+            number: false,
+            character: false,
+        }
+    }
+
+    pub fn line_comment<S: Into<String>>(mut self, leader: S) -> Self {
+        self.line_comments.push(leader.into());
+        self
+    }
+
+    pub fn block_comment<S: Into<String>>(mut self, start: S, end: S) -> Self {
+        self.block_comment = Some((start.into(), end.into()));
+        self
+    }
+
+    pub fn keyword<S: Into<String>>(mut self, keyword: S) -> Self {
+        self.keywords.push(keyword.into());
+        self
+    }
+
+    pub fn control_statement<S: Into<String>>(mut self, keyword: S) -> Self {
+        self.control_statements.push(keyword.into());
+        self
+    }
+
+    pub fn builtin_type<S: Into<String>>(mut self, ty: S) -> Self {
+        self.builtin_types.push(ty.into());
+        self
+    }
+
+    pub fn builtin_function<S: Into<String>>(mut self, name: S) -> Self {
+        self.builtin_functions.push(name.into());
+        self
+    }
+
+    pub fn string_quote(mut self, quote: char) -> Self {
+        self.string_quotes.push(StringKind::new(quote));
+        self
+    }
+
+    pub fn string_kind(mut self, kind: StringKind) -> Self {
+        self.string_quotes.push(kind);
+        self
+    }
+
+    pub fn multiline_strings(mut self, enabled: bool) -> Self {
+        self.multiline_strings = enabled;
+        self
+    }
+
+    pub fn number(mut self, enabled: bool) -> Self {
+        self.number = enabled;
+        self
+    }
+
+    pub fn character(mut self, enabled: bool) -> Self {
+        self.character = enabled;
+        self
+    }
+
+    pub fn build(self) -> SyntaxHighlight {
+        let multiline = self.multiline_strings;
+        let string_quotes: Vec<_> = self
+            .string_quotes
+            .into_iter()
+            .map(|k| if multiline { k.multiline() } else { k })
+            .collect();
+        SyntaxHighlight {
+            lang: Language::Custom(syntax_file::intern_name(&self.name)),
+            line_comments: Words::Owned(self.line_comments),
+            block_comment: self
+                .block_comment
+                .map(|(start, end)| (Cow::Owned(start), Cow::Owned(end))),
+            keywords: Words::Owned(self.keywords),
+            control_statements: Words::Owned(self.control_statements),
+            builtin_types: Words::Owned(self.builtin_types),
+            builtin_functions: Words::Owned(self.builtin_functions),
+            string_quotes: Cow::Owned(string_quotes),
+            number: self.number,
+            character: self.character,
+            ..PLAIN_SYNTAX
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum NumLit {
+    Digit,
+    Hex,
+    Bin,
+    Oct,
+}
+
+enum ParseStep {
+    Ahead(usize),
+    Break,
+}
+
+// Kind of string literal which is now open
+#[derive(PartialEq, Clone, Copy)]
+enum Quote {
+    Single(char),       // "..."
+    Triple(char),       // """...""" (Can span multiple lines)
+    Raw(usize),         // r#"..."#, [==[...]==] in Lua or R"x(...)x" in C++ (Length of delimiter)
+    Interpolated(char), // $"...{x}..." in C#
+}
+
+fn starts_with_triple(input: &str, q: char) -> bool {
+    input.chars().take(3).filter(|c| *c == q).count() == 3
+}
+
+fn starts_with_repeated(input: &str, b: u8, n: usize) -> bool {
+    input.bytes().take(n).filter(|c| *c == b).count() == n
+}
+
+fn is_sep(c: char) -> bool {
+    if c.is_ascii() {
+        c.is_ascii_whitespace() || (c.is_ascii_punctuation() && c != '_') || c == '\0'
+    } else {
+        // Non-ASCII letters, digits and combining marks can be a part of identifier such as 'café'.
+        // Other characters such as '、' or U+3000 (ideographic space) separate words
+        !c.is_alphanumeric() && !is_combining_mark(c)
+    }
+}
+
+fn is_combining_mark(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036f}'
+            | '\u{1ab0}'..='\u{1aff}'
+            | '\u{1dc0}'..='\u{1dff}'
+            | '\u{20d0}'..='\u{20ff}'
+            | '\u{fe20}'..='\u{fe2f}'
+    )
+}
+
+// Odd number of backslashes at end of line escapes the newline
+fn ends_with_line_continuation(row: &str) -> bool {
+    row.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1
+}
+
+// Doc comment leader such as '///' or '/**'. Note that '////' and '/**/' are not doc comments
+fn is_doc_comment(input: &str, leader: &str) -> bool {
+    matches!(input.strip_prefix(leader), Some(rest) if !rest.starts_with('/'))
+}
+
+// Length of escape sequence such as \n, \x41 or \u{1F600}. `input` starts with '\'
+fn escape_len(input: &str) -> usize {
+    let digits = |s: &str, max: usize, radix: u32| {
+        s.chars()
+            .take(max)
+            .take_while(|c| c.is_digit(radix))
+            .count()
+    };
+    let rest = &input[1..];
+    let len = match rest.chars().next() {
+        None => return 1, // Backslash at end of line
+        Some('x') => 1 + digits(&rest[1..], 2, 16),
+        Some('u') if rest[1..].starts_with('{') => {
+            // At most 6 hex digits in braces. Otherwise it is \u followed by a string such as "\u{"
+            let len = digits(&rest[2..], 6, 16);
+            if rest[2 + len..].starts_with('}') {
+                len + 3
+            } else {
+                1
+            }
+        }
+        Some('u') => 1 + digits(&rest[1..], 4, 16),
+        Some('U') => 1 + digits(&rest[1..], 8, 16),
+        Some(c) if c.is_digit(8) => digits(rest, 3, 8),
+        Some(_) => 1,
+    };
+    len + 1
+}
+
+// Length of variable or substitution such as $HOME, ${HOME}, $1 or $(ls) in shell. `input` starts
+// with '$'
+fn variable_len(input: &str) -> Option<usize> {
+    let rest = &input[1..];
+    let close = match rest.chars().next()? {
+        '{' => '}',
+        '(' => ')',
+        c if c.is_ascii_digit() || "@*#?$!-".contains(c) => return Some(2),
+        c if c.is_alphabetic() || c == '_' => {
+            let len = rest
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .count();
+            return Some(len + 1);
+        }
+        _ => return None,
+    };
+
+    // Consider nested parens such as $((1 + 2)). Highlight until end of line when not closed
+    let open = rest.chars().next().unwrap();
+    let mut depth = 0;
+    let mut len = 1;
+    for c in rest.chars() {
+        len += 1;
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                break;
+            }
+        }
+    }
+    Some(len)
+}
+
+// Length of string literal which is a key of object such as "name": in JSON. `input` starts with
+// quote `q`. `sep` separates the key and its value
+fn key_len(input: &str, q: char, seps: &[char]) -> Option<usize> {
+    let mut escaped = false;
+    let mut chars = input.char_indices().skip(1);
+    let end = loop {
+        let (i, c) = chars.next()?; // String is not closed in this line
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == q {
+            break i + 1;
+        }
+    };
+    if input[end..].trim_start().starts_with(seps) {
+        Some(input[..end].chars().count())
+    } else {
+        None
+    }
+}
+
+// Length of date and time such as 1979-05-27T07:32:00Z or 07:32:00 in TOML
+fn datetime_len(input: &str) -> Option<usize> {
+    let b = input.as_bytes();
+    let digits = |n: usize| b.len() > n && b[..n].iter().all(u8::is_ascii_digit);
+    if !(digits(4) && b[4] == b'-' || digits(2) && b[2] == b':') {
+        return None;
+    }
+    let len = input
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || "-:.+TZtz".contains(*c))
+        .count();
+    Some(len)
+}
+
+// ':' followed by whitespace separates key and value of YAML mapping
+fn is_mapping_value(input: &str) -> bool {
+    input.is_empty() || input.starts_with(char::is_whitespace)
+}
+
+// Length of emphasis such as *italic* or __bold__ in Markdown. `input` starts with '*' or '_'
+fn emphasis_len(input: &str, prev_char: char) -> Option<usize> {
+    let c = input.chars().next()?;
+    if c == '_' && !is_sep(prev_char) {
+        return None; // '_' in word such as snake_case is not emphasis
+    }
+    let marker = if input[1..].starts_with(c) {
+        &input[..2]
+    } else {
+        &input[..1]
+    };
+    let body = &input[marker.len()..];
+    if body.is_empty() || body.starts_with(char::is_whitespace) {
+        return None; // e.g. '* ' is an item of list
+    }
+    let end = body.find(marker)?;
+    if end == 0 || body[..end].ends_with(char::is_whitespace) {
+        return None;
+    }
+    Some(input[..marker.len() * 2 + end].chars().count())
+}
+
+// Identifier such as MAX_LEN or PI is a constant by convention. Single letter such as `X` is
+// ambiguous (e.g. type parameter) so it requires at least two characters
+const MIN_CONSTANT_LEN: usize = 2;
+
+fn is_screaming_case(ident: &str) -> bool {
+    ident.chars().count() >= MIN_CONSTANT_LEN
+        && ident.starts_with(|c: char| !c.is_ascii_digit())
+        && ident.chars().any(char::is_alphabetic)
+        && !ident.chars().any(char::is_lowercase)
+}
+
+fn is_word_end(input: &str) -> bool {
+    input.is_empty() || input.starts_with(is_sep)
+}
+
+// Markers in comments such as `// TODO: fix this`. Shared by all languages
+const TODO_MARKERS: &[&str] = &["TODO", "FIXME", "XXX", "HACK", "NOTE"];
+
+// Repaint markers in comments after highlighting a line. Only whole words are matched
+fn highlight_todo_markers(out: &mut [Highlight], row: &str) {
+    let mut prev_char = '\0';
+    for (x, (idx, c)) in row.char_indices().enumerate() {
+        let in_comment = matches!(out[x], Highlight::Comment | Highlight::DocComment);
+        if in_comment && is_sep(prev_char) {
+            let input = &row[idx..];
+            if let Some(marker) = TODO_MARKERS
+                .iter()
+                .find(|m| input.starts_with(*m) && is_word_end(&input[m.len()..]))
+            {
+                for hl in out[x..x + marker.len()].iter_mut() {
+                    *hl = Highlight::Todo;
+                }
+            }
+        }
+        prev_char = c;
+    }
+}
+
+// URLs in comments such as `// see https://example.com`. Shared by all languages
+const LINK_SCHEMES: &[&str] = &["https://", "http://", "file://"];
+
+// Repaint URLs in comments after highlighting a line. URL ends at whitespace or closing bracket
+fn highlight_links(out: &mut [Highlight], row: &str) {
+    let mut prev_char = '\0';
+    let mut iter = row.char_indices().enumerate();
+    while let Some((x, (idx, c))) = iter.next() {
+        let in_comment = matches!(out[x], Highlight::Comment | Highlight::DocComment);
+        let input = &row[idx..];
+        if in_comment && is_sep(prev_char) && LINK_SCHEMES.iter().any(|s| input.starts_with(s)) {
+            let end = input
+                .find(|c: char| c.is_whitespace() || ")]}>\"'`".contains(c))
+                .unwrap_or(input.len());
+            // Punctuation at end of sentence such as `see https://example.com.` is not a part of URL
+            let url = input[..end].trim_end_matches(['.', ',', ';', ':']);
+            let len = url.chars().count();
+            for hl in out[x..].iter_mut().take(len) {
+                *hl = Highlight::Link;
+            }
+            prev_char = url.chars().next_back().unwrap();
+            iter.nth(len - 2);
+            continue;
+        }
+        prev_char = c;
+    }
+}
+
+// '.' following digits is a decimal point unless it is a part of range (1..2) or method call (1.max(2))
+fn is_decimal_point(input: &str) -> bool {
+    match input[1..].chars().next() {
+        Some(c) => c != '.' && c != '_' && !c.is_alphabetic(),
+        None => true,
+    }
+}
+
+// Returns length of exponent part of float number literal such as 'e10' or 'E-3'. `marker` is
+// "eE" for decimal numbers and "pP" for hexadecimal numbers
+fn exponent_len(input: &str, marker: &str) -> Option<usize> {
+    let mut i = input.chars();
+    if !marker.contains(i.next()?) {
+        return None;
+    }
+    match (i.next(), i.next()) {
+        (Some(d), _) if d.is_ascii_digit() => Some(1),
+        (Some('+') | Some('-'), Some(d)) if d.is_ascii_digit() => Some(2),
+        _ => None,
+    }
+}
+
+// States which are carried over lines. State at end of each line is cached to restart highlighting
+// from the middle of buffer
+#[derive(Clone, Copy, PartialEq, Default)]
+struct LineState {
+    prev_quote: Option<Quote>,
+    raw_prefix: bool, // String literal has raw prefix such as r"..." in Python. No escape in it
+    block_comment_depth: usize,
+    in_doc_comment: bool,
+    in_line_head_block_comment: bool,
+    in_tag: bool,
+    in_fenced_code: bool,
+    brace_depth: usize,
+}
+
+// Highlights of keywords, control statements, builtin types, boolean constants, special
+// variables and builtin functions. Built once per language since identifiers are looked up at every word boundary
+struct KeywordTable {
+    words: HashMap<String, Highlight>,
+    case_insensitive: bool, // When true, keys are in lowercase
+}
+
+impl KeywordTable {
+    fn new(syntax: &SyntaxHighlight) -> Self {
+        let lists = [
+            (&syntax.keywords, Highlight::Keyword),
+            (&syntax.control_statements, Highlight::Statement),
+            (&syntax.builtin_types, Highlight::Type),
+            (&Words::Static(syntax.boolean_constants), Highlight::Boolean),
+            (&Words::Static(syntax.special_vars), Highlight::SpecialVar),
+            (&syntax.builtin_functions, Highlight::Builtin),
+        ];
+        let case_insensitive = syntax.case_insensitive_keywords;
+        let mut words = HashMap::new();
+        for (list, hl) in lists.iter() {
+            for word in list.iter() {
+                let word = if case_insensitive {
+                    word.to_ascii_lowercase()
+                } else {
+                    word.to_string()
+                };
+                // The first list wins when a word is in multiple lists
+                words.entry(word).or_insert(*hl);
+            }
+        }
+        Self {
+            words,
+            case_insensitive,
+        }
+    }
+
+    fn get(&self, ident: &str) -> Option<Highlight> {
+        if self.case_insensitive {
+            // e.g. Both SELECT and select in SQL
+            self.words.get(&ident.to_ascii_lowercase()).copied()
+        } else {
+            self.words.get(ident).copied()
+        }
+    }
+}
+
+struct Highlighter<'a> {
+    syntax: &'a SyntaxHighlight,
+    keywords: &'a KeywordTable,
+    state: LineState,
+    prev_hl: Highlight,
+    prev_char: char,
+    // Last non-whitespace character and its highlight in the line
+    prev_token: (char, Highlight),
+    num: NumLit,
+    after_def_keyword: bool,
+}
+
+impl<'a> Highlighter<'a> {
+    fn new<'b: 'a>(
+        syntax: &'b SyntaxHighlight,
+        keywords: &'b KeywordTable,
+        state: LineState,
+    ) -> Self {
+        Self {
+            syntax,
+            keywords,
+            state,
+            prev_hl: Highlight::Normal,
+            prev_char: '\0',
+            prev_token: ('\0', Highlight::Normal),
+            num: NumLit::Digit,
+            after_def_keyword: false,
+        }
+    }
+
+    // Note that `len` is a number of characters, not a number of bytes
+    fn eat_n(
+        &mut self,
+        out: &mut [Highlight],
+        input: &str,
+        hl: Highlight,
+        len: usize,
+    ) -> ParseStep {
+        debug_assert!(len > 0);
+        debug_assert!(!input.is_empty());
+        debug_assert!(!out.is_empty());
+
+        for out in out.iter_mut().take(len) {
+            *out = hl;
+        }
+        self.prev_hl = hl;
+        self.prev_char = input.chars().nth(len - 1).unwrap();
+        if !self.prev_char.is_whitespace() {
+            self.prev_token = (self.prev_char, hl);
+        }
+        ParseStep::Ahead(len)
+    }
+
+    fn eat_one(&mut self, out: &mut [Highlight], c: char, hl: Highlight) -> ParseStep {
+        out[0] = hl;
+        self.prev_hl = hl;
+        self.prev_char = c;
+        if !c.is_whitespace() {
+            self.prev_token = (c, hl);
+        }
+        ParseStep::Ahead(1)
+    }
+
+    fn highlight_block_comment(
+        &mut self,
+        start: &str,
+        end: &str,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        if self.state.prev_quote.is_some() {
+            return None;
+        }
+
+        let in_comment = self.state.block_comment_depth > 0;
+        if !in_comment {
+            self.state.in_doc_comment =
+                matches!(self.syntax.doc_block_comment, Some(doc) if is_doc_comment(input, doc));
+        }
+        let hl = if self.state.in_doc_comment {
+            Highlight::DocComment
+        } else {
+            Highlight::Comment
+        };
+
+        // End is checked before start so that the comment toggles on each occurrence of delimiter
+        // when start and end are the same string such as ''' in syntax loaded from file
+        let comment_delim = if in_comment && input.starts_with(end) {
+            self.state.block_comment_depth -= 1;
+            end
+        } else if (!in_comment || self.syntax.nested_block_comment) && input.starts_with(start) {
+            self.state.block_comment_depth += 1;
+            start
+        } else {
+            return if in_comment {
+                Some(self.eat_one(out, c, hl))
+            } else {
+                None
+            };
+        };
+
+        // Consume whole '/*' here. Otherwise such as '/*/' is wrongly accepted
+        Some(self.eat_n(out, input, hl, comment_delim.chars().count()))
+    }
+
+    fn highlight_statement_comment(
+        &mut self,
+        leader: &str,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        // Comment only at start of statement such as `" foo` or `set nu | " foo` in Vim script.
+        // Elsewhere the leader starts a string literal
+        if matches!(self.prev_token, ('\0', _) | ('|', Highlight::Normal)) {
+            self.highlight_line_comment(leader, out, input)
+        } else {
+            None
+        }
+    }
+
+    fn highlight_line_comment(
+        &mut self,
+        leader: &str,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        if self.state.prev_quote.is_none() && input.starts_with(leader) {
+            let comment = if self
+                .syntax
+                .doc_line_comments
+                .iter()
+                .any(|doc| is_doc_comment(input, doc))
+            {
+                Highlight::DocComment
+            } else {
+                Highlight::Comment
+            };
+            // Highlight as comment until end of line
+            for hl in out.iter_mut() {
+                *hl = comment;
+            }
+            Some(ParseStep::Break)
+        } else {
+            None
+        }
+    }
+
+    // Length of prefix of string or character literal such as f"..." in Python or b'a' in Rust
+    fn literal_prefix_len(&self, input: &str, is_quote: impl Fn(char) -> bool) -> usize {
+        if !is_sep(self.prev_char) {
+            return 0; // Prefix must be at start of word. e.g. `elif"foo"` is not a prefixed string
+        }
+        self.syntax
+            .string_prefixes
+            .iter()
+            .find(|p| input.starts_with(*p) && input[p.len()..].starts_with(&is_quote))
+            .map(|p| p.len())
+            .unwrap_or(0)
+    }
+
+    // Returns number of '#'s and length of start delimiter when the input starts with raw string
+    // literal such as r#"..."# or br"..."
+    fn raw_string_start(&self, input: &str) -> Option<(usize, usize)> {
+        if self.syntax.long_brackets {
+            // Long bracket such as [[...]] or [==[...]==] in Lua
+            let rest = input.strip_prefix('[')?;
+            let level = rest.bytes().take_while(|b| *b == b'=').count();
+            return if rest[level..].starts_with('[') {
+                Some((level, level + 2))
+            } else {
+                None
+            };
+        }
+        if self.syntax.delimited_raw_string {
+            return self.delimited_raw_string_start(input);
+        }
+        if self.syntax.verbatim_strings {
+            // Verbatim string such as @"C:\dir" in C#. Interpolation in $@"..." is not highlighted
+            if !is_sep(self.prev_char) {
+                return None;
+            }
+            let rest = ["@$", "$@", "@"]
+                .iter()
+                .find_map(|p| input.strip_prefix(p))?
+                .strip_prefix('"')?;
+            return Some((0, input.len() - rest.len()));
+        }
+        if !self.syntax.raw_string || !is_sep(self.prev_char) {
+            return None;
+        }
+        let rest = input.strip_prefix('b').unwrap_or(input).strip_prefix('r')?;
+        let prefix_len = input.len() - rest.len();
+        let hashes = rest.bytes().take_while(|b| *b == b'#').count();
+        if rest[hashes..].starts_with('"') {
+            Some((hashes, prefix_len + hashes + 1))
+        } else {
+            None
+        }
+    }
+
+    // Raw string literal with delimiter such as R"(...)" or u8R"abc(...)abc" in C++
+    fn delimited_raw_string_start(&self, input: &str) -> Option<(usize, usize)> {
+        if !is_sep(self.prev_char) {
+            return None;
+        }
+        let rest = ["u8R", "uR", "UR", "LR", "R"]
+            .iter()
+            .find_map(|p| input.strip_prefix(p))?
+            .strip_prefix('"')?;
+        let delim = rest.find('(')?;
+        if delim > 16 || rest[..delim].contains(|c: char| c.is_whitespace() || "\\)\"".contains(c))
+        {
+            return None;
+        }
+        Some((delim, input.len() - rest.len() + delim + 1))
+    }
+
+    // Returns length of end delimiter when the input starts with the end of raw string
+    fn raw_string_end_len(&self, input: &str, level: usize) -> Option<usize> {
+        if self.syntax.delimited_raw_string {
+            // Only length of the delimiter is remembered. Its characters are not compared
+            let rest = input.strip_prefix(')')?;
+            match rest.get(..level) {
+                Some(delim) if !delim.contains('"') && rest[level..].starts_with('"') => {
+                    Some(level + 2)
+                }
+                _ => None,
+            }
+        } else if self.syntax.long_brackets {
+            let rest = input.strip_prefix(']')?;
+            if starts_with_repeated(rest, b'=', level) && rest[level..].starts_with(']') {
+                Some(level + 2)
+            } else {
+                None
+            }
+        } else if starts_with_repeated(input.strip_prefix('"')?, b'#', level) {
+            Some(level + 1)
+        } else {
+            None
+        }
+    }
+
+    fn highlight_string(
+        &mut self,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        if let Some(quote) = self.state.prev_quote {
+            // In string literal
+            match quote {
+                Quote::Raw(_) if self.syntax.verbatim_strings && input.starts_with("\"\"") => {
+                    // "" is an escaped quote in verbatim string such as @"say ""hi"""
+                    Some(self.eat_n(out, input, Highlight::String, 2))
+                }
+                Quote::Raw(level) => match self.raw_string_end_len(input, level) {
+                    Some(len) => {
+                        self.state.prev_quote = None;
+                        Some(self.eat_n(out, input, Highlight::String, len))
+                    }
+                    None => Some(self.eat_one(out, c, Highlight::String)), // No escape in raw string
+                },
+                Quote::Single(q) if self.syntax.string_kind(q).is_some_and(|k| !k.escapes) => {
+                    // No escape nor variable in such as '...' in shell
+                    if q == c {
+                        self.state.prev_quote = None;
+                        self.state.raw_prefix = false;
+                    }
+                    Some(self.eat_one(out, c, Highlight::String))
+                }
+                Quote::Interpolated(_) if input.starts_with("{{") => {
+                    Some(self.eat_n(out, input, Highlight::String, 2)) // Escaped brace
+                }
+                Quote::Interpolated(_) if c == '{' => {
+                    // Nested braces are not considered since they are rare in interpolation
+                    let len = match input.find('}') {
+                        Some(i) => input[..=i].chars().count(),
+                        None => input.chars().count(),
+                    };
+                    Some(self.eat_n(out, input, Highlight::Variable, len))
+                }
+                _ if c == '\\'
+                    && self.syntax.paren_interpolation
+                    && input[1..].starts_with('(') =>
+                {
+                    // Interpolation such as \(x) in Swift. Nested parens are considered
+                    let len = variable_len(input).unwrap();
+                    Some(self.eat_n(out, input, Highlight::Variable, len))
+                }
+                _ if c == '#' && self.syntax.hash_interpolation && input[1..].starts_with('{') => {
+                    // Interpolation such as #{x} in Elixir
+                    let len = variable_len(input).unwrap();
+                    Some(self.eat_n(out, input, Highlight::Variable, len))
+                }
+                _ if c == '\\' && self.state.raw_prefix => {
+                    // No escape in raw string, but a quote after backslash does not end the string
+                    let len = input.chars().take(2).count();
+                    Some(self.eat_n(out, input, Highlight::String, len))
+                }
+                _ if c == '\\' => {
+                    Some(self.eat_n(out, input, Highlight::Escape, escape_len(input)))
+                }
+                _ if c == '$' && self.syntax.dollar_variables => match variable_len(input) {
+                    Some(len) => Some(self.eat_n(out, input, Highlight::Variable, len)),
+                    None => Some(self.eat_one(out, c, Highlight::String)),
+                },
+                Quote::Triple(q) if starts_with_triple(input, q) => {
+                    self.state.prev_quote = None;
+                    self.state.raw_prefix = false;
+                    Some(self.eat_n(out, input, Highlight::String, 3))
+                }
+                Quote::Single(q) | Quote::Interpolated(q) if q == c => {
+                    self.state.prev_quote = None;
+                    self.state.raw_prefix = false;
+                    Some(self.eat_one(out, c, Highlight::String))
+                }
+                _ => Some(self.eat_one(out, c, Highlight::String)),
+            }
+        } else if let Some((hashes, len)) = self.raw_string_start(input) {
+            self.state.prev_quote = Some(Quote::Raw(hashes));
+            Some(self.eat_n(out, input, Highlight::String, len))
+        } else {
+            // Prefix such as f"..." in Python is highlighted as a part of the string literal
+            let prefix_len =
+                self.literal_prefix_len(input, |c| self.syntax.string_kind(c).is_some());
+            let q = input[prefix_len..].chars().next()?;
+            let (quote, len) = if self.syntax.triple_quotes.contains(&q)
+                && starts_with_triple(&input[prefix_len..], q)
+            {
+                (Quote::Triple(q), 3)
+            } else if self.syntax.string_kind(q).is_some() {
+                let prefix = &input[..prefix_len];
+                if self.syntax.interpolated_prefixes.contains(&prefix) {
+                    (Quote::Interpolated(q), 1)
+                } else {
+                    (Quote::Single(q), 1)
+                }
+            } else {
+                return None;
+            };
+            if let (true, Some(len)) = (
+                self.syntax.object_keys,
+                key_len(&input[prefix_len..], q, &[':']),
+            ) {
+                return Some(self.eat_n(out, input, Highlight::Key, prefix_len + len));
+            }
+            self.state.prev_quote = Some(quote);
+            self.state.raw_prefix = input[..prefix_len].contains(['r', 'R']);
+            Some(self.eat_n(out, input, Highlight::String, prefix_len + len))
+        }
+    }
+
+    fn highlight_sigil(
+        &mut self,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        // Sigil such as ~s(foo), ~w[a b] or ~r/ab+/i in Elixir. Name of uppercase sigil can be
+        // longer such as ~HTML and escape sequences are not processed in it
+        if c != '~' || !is_sep(self.prev_char) {
+            return None;
+        }
+        let name: String = input[1..]
+            .chars()
+            .take_while(char::is_ascii_alphanumeric)
+            .collect();
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        let mut chars = input[name.len() + 1..].chars();
+        let open = chars.next()?;
+        let close = match open {
+            '(' => ')',
+            '[' => ']',
+            '{' => '}',
+            '<' => '>',
+            '/' | '|' | '"' | '\'' => open,
+            _ => return None,
+        };
+
+        let escape = name.starts_with(|c: char| c.is_ascii_lowercase());
+        let mut escaped = false;
+        let mut len = name.len() + 2;
+        loop {
+            let c = chars.next()?; // Sigil is not closed in this line
+            len += 1;
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if escape => escaped = true,
+                c if c == close => break,
+                _ => {}
+            }
+        }
+        let modifiers = chars.take_while(char::is_ascii_alphabetic).count();
+
+        let hl = if name == "r" || name == "R" {
+            Highlight::Regex
+        } else {
+            Highlight::String
+        };
+        Some(self.eat_n(out, input, hl, len + modifiers))
+    }
+
+    fn highlight_quote_operator(
+        &mut self,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        // Quote-like operator such as q(foo), qq{a $b} or qw/a b/ in Perl. Paired delimiters can be
+        // nested like q(a (b) c)
+        if !c.is_ascii_alphabetic() || !is_sep(self.prev_char) {
+            return None;
+        }
+        let name = self
+            .syntax
+            .quote_operators
+            .iter()
+            .find(|op| input.starts_with(*op) && input[op.len()..].starts_with(is_sep))?;
+        let mut chars = input[name.len()..].chars();
+        let open = chars.next()?;
+        let close = match open {
+            '(' => ')',
+            '[' => ']',
+            '{' => '}',
+            '<' => '>',
+            '/' | '|' | '!' | '"' | '\'' => open,
+            _ => return None,
+        };
+
+        let mut depth = 1;
+        let mut escaped = false;
+        let mut len = name.len() + 1;
+        while depth > 0 {
+            let c = chars.next()?; // Not closed in this line
+            len += 1;
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                c if c == close => depth -= 1,
+                c if c == open => depth += 1,
+                _ => {}
+            }
+        }
+        Some(self.eat_n(out, input, Highlight::String, len))
+    }
+
+    fn highlight_line_string(
+        &mut self,
+        prefix: &str,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        // Each line of multiline string such as \\foo in Zig continues until end of line. Escape
+        // sequences are not processed
+        if self.state.prev_quote.is_some() || !input.starts_with(prefix) {
+            return None;
+        }
+        for hl in out.iter_mut() {
+            *hl = Highlight::String;
+        }
+        Some(ParseStep::Break)
+    }
+
+    fn highlight_symbol(
+        &mut self,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        // Symbol literal such as :foo. Note that `::` and `foo:` are not symbols
+        if c != ':' || self.prev_char == ':' || !is_sep(self.prev_char) {
+            return None;
+        }
+        let mut chars = input[1..].chars();
+        match chars.next() {
+            Some(c) if !is_sep(c) && !c.is_ascii_digit() => {
+                let len = chars.take_while(|c| !is_sep(*c)).count() + 2;
+                Some(self.eat_n(out, input, Highlight::Symbol, len))
+            }
+            _ => None,
+        }
+    }
+
+    fn highlight_decorator(
+        &mut self,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        // Decorator such as @media in CSS or module attribute such as @doc in Elixir
+        if c != '@' || !is_sep(self.prev_char) {
+            return None;
+        }
+        let len = input[1..].chars().take_while(|c| !is_sep(*c)).count();
+        if len == 0 {
+            return None;
+        }
+        Some(self.eat_n(out, input, Highlight::Attribute, len + 1))
+    }
+
+    fn highlight_annotation(
+        &mut self,
+        sigil: char,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        // Annotation such as @Override in Java or decorator such as @app.route("/") in Python
+        if c != sigil || !is_sep(self.prev_char) {
+            return None;
+        }
+        let is_ident_char = |c: char| c.is_alphanumeric() || c == '_';
+        let mut end = c.len_utf8();
+        loop {
+            let len = input[end..]
+                .find(|c| !is_ident_char(c))
+                .unwrap_or(input.len() - end);
+            if len == 0 {
+                return None; // Only the first name can be empty. e.g. `a @ b` in Python
+            }
+            end += len;
+            if input[end..].starts_with('.') && input[end + 1..].starts_with(is_ident_char) {
+                end += 1;
+            } else {
+                break;
+            }
+        }
+        let len = input[..end].chars().count();
+        Some(self.eat_n(out, input, Highlight::Annotation, len))
+    }
+
+    fn highlight_at_name(
+        &mut self,
+        hl: Highlight,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        // Builtin function such as @import("std") in Zig or macro such as @time in Julia
+        if c != '@' || !is_sep(self.prev_char) {
+            return None;
+        }
+        let len = input[1..]
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .count();
+        if len == 0 {
+            return None;
+        }
+        Some(self.eat_n(out, input, hl, len + 1))
+    }
+
+    fn highlight_dot_directive(
+        &mut self,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        // Directive such as .text or .globl in assembly. It must be the first token of statement so
+        // that a reference to local label such as `jmp .L1` is not a directive
+        if c != '.'
+            || !matches!(self.prev_token.0, '\0' | ':')
+            || !input[1..].starts_with(char::is_alphabetic)
+        {
+            return None;
+        }
+        let len = input[1..].chars().take_while(|c| !is_sep(*c)).count();
+        Some(self.eat_n(out, input, Highlight::Keyword, len + 1))
+    }
+
+    fn highlight_immediate(
+        &mut self,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        // Immediate value such as $0x10 in GAS or #5 in ARM assembly
+        if !self.syntax.immediate_prefixes.contains(&c) || !is_sep(self.prev_char) {
+            return None;
+        }
+        let rest = &input[c.len_utf8()..];
+        let digits = rest.strip_prefix('-').unwrap_or(rest);
+        if !digits.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        let len = digits
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(digits.len());
+        let len = 1 + rest.len() - digits.len() + len;
+        Some(self.eat_n(out, input, Highlight::Number, len))
+    }
+
+    fn highlight_hash_directive(
+        &mut self,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        // Compiler directive such as #if or #available(...) in Swift. Unlike C preprocessor, it can
+        // appear in the middle of line
+        if c != '#' || !is_sep(self.prev_char) || !input[1..].starts_with(char::is_alphabetic) {
+            return None;
+        }
+        let len = input[1..].chars().take_while(|c| !is_sep(*c)).count();
+        Some(self.eat_n(out, input, Highlight::Preprocessor, len + 1))
+    }
+
+    fn highlight_attribute(
+        &mut self,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        // Attribute such as #[derive(Debug)] or #![allow(unused)] in Rust
+        if c != '#' {
+            return None;
+        }
+        let start = if input.starts_with("#[") {
+            1
+        } else if input.starts_with("#![") {
+            2
+        } else {
+            return None;
+        };
+
+        let mut depth = 0;
+        let mut len = start;
+        for c in input[start..].chars() {
+            len += 1;
+            match c {
+                '[' => depth += 1,
+                ']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Some(self.eat_n(out, input, Highlight::Attribute, len))
+    }
+
+    fn highlight_hex_color(
+        &mut self,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        // Hex color such as #fff or #ff0000aa in CSS. Note that #abc in selector is an ID
+        if c != '#' || self.state.brace_depth == 0 {
+            return None;
+        }
+        let len = input[1..].bytes().take_while(u8::is_ascii_hexdigit).count();
+        if matches!(len, 3 | 4 | 6 | 8) && is_word_end(&input[len + 1..]) {
+            Some(self.eat_n(out, input, Highlight::Number, len + 1))
+        } else {
+            None
+        }
+    }
+
+    fn highlight_property(
+        &mut self,
+        hl: Highlight,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        // Property name such as `font-size:` in CSS declaration block or field name such as `id:`
+        // in GraphQL type definition
+        if self.state.brace_depth == 0 || !is_sep(self.prev_char) {
+            return None;
+        }
+        let len = input
+            .find(|c: char| !c.is_alphanumeric() && c != '-' && c != '_')
+            .unwrap_or(input.len());
+        if len == 0 || !input[len..].trim_start().starts_with(':') {
+            return None;
+        }
+        let len = input[..len].chars().count();
+        Some(self.eat_n(out, input, hl, len))
+    }
+
+    fn highlight_markup(
+        &mut self,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        fn name_len(input: &str) -> usize {
+            input
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '-' || *c == '_' || *c == ':')
+                .count()
+        }
+
+        if self.state.prev_quote.is_some() {
+            return None;
+        }
+
+        if self.state.in_tag {
+            // Attribute names and closing '>' in tag such as <a href="...">. Values are strings
+            if c == '>' {
+                self.state.in_tag = false;
+                return Some(self.eat_one(out, c, Highlight::Normal));
+            }
+            if !is_sep(self.prev_char) {
+                return None;
+            }
+            return match name_len(input) {
+                0 => None,
+                len => Some(self.eat_n(out, input, Highlight::Attribute, len)),
+            };
+        }
+
+        match c {
+            '<' => {
+                // Start of tag such as <p> or </p>. Tag name must start with alphabet (not <!DOCTYPE)
+                let start = if input[1..].starts_with('/') { 2 } else { 1 };
+                if !input[start..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+                    return None;
+                }
+                self.state.in_tag = true;
+                let len = start + name_len(&input[start..]);
+                Some(self.eat_n(out, input, Highlight::Keyword, len))
+            }
+            '&' => {
+                // Character reference such as &amp; or &#x27;
+                let end = input[1..].find(';')?;
+                let name = &input[1..end + 1];
+                if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '#') {
+                    return None;
+                }
+                Some(self.eat_n(out, input, Highlight::Escape, end + 2))
+            }
+            _ => None,
+        }
+    }
+
+    fn highlight_variable(
+        &mut self,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        if self.syntax.sigil_variables && (c == '@' || c == '%') && is_sep(self.prev_char) {
+            // Array or hash such as @list, %map or @$ref in Perl. Note that `$a % $b` is modulo
+            let len = match input[1..].chars().next()? {
+                '$' => variable_len(&input[1..])? + 1,
+                c if c.is_alphabetic() || c == '_' || c == '{' => variable_len(input)?,
+                _ => return None,
+            };
+            return Some(self.eat_n(out, input, Highlight::Variable, len));
+        }
+        if c != '$' {
+            return None;
+        }
+        // Automatic variable such as $@, $< or $^ in Makefile
+        if self.syntax.makefile && input[1..].starts_with(|c: char| "@<^+?*%|".contains(c)) {
+            return Some(self.eat_n(out, input, Highlight::Variable, 2));
+        }
+        let len = variable_len(input)?;
+        Some(self.eat_n(out, input, Highlight::Variable, len))
+    }
+
+    fn highlight_scoped_variable(
+        &mut self,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        // Variable with scope such as g:foo or s:bar#baz in Vim script
+        let scope = self
+            .syntax
+            .variable_scopes
+            .iter()
+            .find(|s| input.starts_with(*s))?;
+        let len = input[scope.len()..]
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '#')
+            .count();
+        if len == 0 {
+            return None;
+        }
+        Some(self.eat_n(out, input, Highlight::Variable, scope.len() + len))
+    }
+
+    // Statement label heuristic. Label is the first token of statement such as `cleanup:` in C
+    fn at_statement_start(&self) -> bool {
+        matches!(self.prev_token.0, '\0' | '{' | '}' | ';')
+    }
+
+    // Label after keyword such as `goto cleanup` in C or `break 'outer` in Rust
+    fn highlight_label_ref(
+        &mut self,
+        keyword: Highlight,
+        ident: &str,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        let rest = &input[ident.len()..];
+        let target = rest.trim_start_matches(' ');
+        let spaces = rest.len() - target.len();
+        // Labels are lifetimes in Rust. `break value` is not a label
+        let sigil = usize::from(self.syntax.lifetime);
+        if spaces == 0 || (sigil > 0 && !target.starts_with('\'')) {
+            return None;
+        }
+        let name = &target[sigil..];
+        if !name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+            return None;
+        }
+        let name = &name[..name.find(is_sep).unwrap_or(name.len())];
+
+        let keyword_len = ident.chars().count();
+        let label_len = sigil + name.chars().count();
+        for (i, hl) in out
+            .iter_mut()
+            .take(keyword_len + spaces + label_len)
+            .enumerate()
+        {
+            *hl = if i < keyword_len {
+                keyword
+            } else if i < keyword_len + spaces {
+                Highlight::Normal
+            } else {
+                Highlight::Label
+            };
+        }
+        self.prev_hl = Highlight::Label;
+        self.prev_char = name.chars().last().unwrap();
+        self.prev_token = (self.prev_char, Highlight::Label);
+        Some(ParseStep::Ahead(keyword_len + spaces + label_len))
+    }
+
+    fn highlight_ident(&mut self, out: &mut [Highlight], input: &str) -> Option<ParseStep> {
+        // Slice the identifier once and categorize it. Keywords are not matched as prefixes so
+        // `structure` is not `struct`
+        let len = input.find(is_sep).unwrap_or(input.len());
+        if len == 0 {
+            return None;
+        }
+        let (ident, rest) = input.split_at(len);
+        let keyword = self.keywords.get(ident);
+
+        let definition = if self.after_def_keyword {
+            Some(Highlight::Definition)
+        } else {
+            None
+        };
+        if keyword.is_some() && self.syntax.definition_keywords.contains(&ident) {
+            self.after_def_keyword = true;
+        }
+
+        if let Some(hl) = keyword {
+            if self.syntax.label_keywords.contains(&ident) {
+                if let Some(step) = self.highlight_label_ref(hl, ident, out, input) {
+                    return Some(step);
+                }
+            }
+        }
+
+        // Identifier followed by ':' at statement start is a label such as `cleanup:` in C. Labels
+        // are lifetimes in Rust
+        if keyword.is_none()
+            && self.syntax.statement_labels
+            && !self.syntax.lifetime
+            && rest.starts_with(':')
+            && !rest.starts_with("::")
+            && self.at_statement_start()
+        {
+            return Some(self.eat_n(out, input, Highlight::Label, ident.chars().count()));
+        }
+
+        // Identifier followed by '!' is a macro invocation such as println!() in Rust
+        if keyword.is_none()
+            && self.syntax.macro_bang
+            && rest.starts_with('!')
+            && !rest.starts_with("!=")
+        {
+            let len = ident.chars().count() + 1;
+            return Some(self.eat_n(out, input, Highlight::Macro, len));
+        }
+
+        // Identifier followed by '(' is a function call or a function definition
+        let function = if self.syntax.highlight_functions
+            && !ident.starts_with(|c: char| c.is_ascii_digit())
+            && rest.trim_start().starts_with('(')
+        {
+            Some(Highlight::Function)
+        } else {
+            None
+        };
+
+        let constant = if self.syntax.highlight_screaming_case && is_screaming_case(ident) {
+            Some(Highlight::Constant)
+        } else {
+            None
+        };
+
+        // Capitalized identifier is a type or a data constructor such as `Just` in Haskell
+        let ty = if self.syntax.highlight_capitalized_types
+            && ident.starts_with(|c: char| c.is_uppercase())
+        {
+            Some(Highlight::Type)
+        } else {
+            None
+        };
+
+        // Definition such as `def len(self)` shadows builtin function
+        let hl = match keyword {
+            Some(Highlight::Builtin) => definition.or(keyword),
+            _ => keyword.or(definition),
+        };
+        let hl = hl.or(function).or(constant).or(ty)?;
+        Some(self.eat_n(out, input, hl, ident.chars().count()))
+    }
+
+    fn highlight_prefix_number(
+        &mut self,
+        num: NumLit,
+        is_bound: bool,
+        c: char,
         out: &mut [Highlight],
         input: &str,
-        hl: Highlight,
-        len: usize,
-    ) -> ParseStep {
-        debug_assert!(len > 0);
-        debug_assert!(!input.is_empty());
-        debug_assert!(!out.is_empty());
+    ) -> Option<ParseStep> {
+        let prefix: &[_] = match num {
+            NumLit::Hex => b"0x",
+            NumLit::Bin => b"0b",
+            NumLit::Oct => b"0o",
+            NumLit::Digit => unreachable!(),
+        };
+
+        fn is_num_char(b: u8, num: NumLit, delim: Option<char>) -> bool {
+            match num {
+                NumLit::Hex if b.is_ascii_hexdigit() => true,
+                NumLit::Bin if b"01".contains(&b) => true,
+                NumLit::Oct if (b'0'..=b'7').contains(&b) => true,
+                _ => delim == Some(b as char),
+            }
+        }
+
+        let bytes = input.as_bytes();
+        if is_bound {
+            if bytes.starts_with(prefix)
+                && bytes.len() > prefix.len()
+                && is_num_char(bytes[prefix.len()], num, self.syntax.number_delim)
+            {
+                self.num = num;
+                return Some(self.eat_n(out, input, Highlight::Number, prefix.len()));
+            }
+        } else if self.num == num
+            && self.prev_hl == Highlight::Number
+            && c.is_ascii()
+            && is_num_char(c as u8, num, self.syntax.number_delim)
+        {
+            return Some(self.eat_one(out, c, Highlight::Number));
+        }
+
+        // Hexadecimal float such as 0x1.8p-2. Exponent part is decimal
+        if num == NumLit::Hex
+            && self.syntax.hex_float
+            && self.num == num
+            && self.prev_hl == Highlight::Number
+        {
+            // Fraction part. '.' is a word boundary so the first digit after it is bound
+            let is_point = c == '.'
+                && input[1..].starts_with(|c: char| c.is_ascii_hexdigit() || c == 'p' || c == 'P');
+            if is_point || self.prev_char == '.' && c.is_ascii_hexdigit() {
+                return Some(self.eat_one(out, c, Highlight::Number));
+            }
+            if let Some(len) = exponent_len(input, "pP") {
+                self.num = NumLit::Digit;
+                return Some(self.eat_n(out, input, Highlight::Number, len));
+            }
+        }
+
+        None
+    }
+
+    fn highlight_digit_number(
+        &mut self,
+        is_bound: bool,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        let prev_is_number = self.num == NumLit::Digit && self.prev_hl == Highlight::Number;
+
+        // Float number starting with '.' such as .5. '.' after a word is not a word boundary. Member
+        // access such as `obj.5` and range such as `1..5` are not numbers
+        if self.syntax.leading_decimal_point
+            && c == '.'
+            && !prev_is_number
+            && is_sep(self.prev_char)
+            && !matches!(self.prev_char, '.' | ')' | ']')
+            && input[1..].starts_with(|c: char| c.is_ascii_digit())
+        {
+            self.num = NumLit::Digit;
+            return Some(self.eat_one(out, c, Highlight::Number));
+        }
+
+        if is_bound {
+            if let (true, Some(len)) = (self.syntax.datetime, datetime_len(input)) {
+                self.num = NumLit::Digit;
+                return Some(self.eat_n(out, input, Highlight::Number, len));
+            }
+            if c.is_ascii_digit() || prev_is_number && c == '.' && is_decimal_point(input) {
+                self.num = NumLit::Digit;
+                return Some(self.eat_one(out, c, Highlight::Number));
+            }
+        } else if prev_is_number {
+            if self.syntax.number_delim == Some(c) || c.is_ascii_digit() {
+                return Some(self.eat_one(out, c, Highlight::Number));
+            }
+
+            if let Some(len) = exponent_len(input, "eE") {
+                return Some(self.eat_n(out, input, Highlight::Number, len));
+            }
+        }
+
+        if prev_is_number {
+            // Suffix of number literal such as 1u32 in Rust, 1.0f in C or 50% in CSS
+            let suffix = self
+                .syntax
+                .number_suffixes
+                .iter()
+                .find(|s| input.starts_with(*s) && is_word_end(&input[s.len()..]));
+            if let Some(suffix) = suffix {
+                return Some(self.eat_n(out, input, Highlight::Number, suffix.len()));
+            }
+        }
+
+        None
+    }
+
+    fn highlight_char(&mut self, out: &mut [Highlight], input: &str) -> Option<ParseStep> {
+        if self.syntax.number_delim == Some('\'') && self.prev_hl == Highlight::Number {
+            return None; // Consider number literal delimiter in C++ (e.g. `123'456'789`)
+        }
+
+        let prefix_len = self.literal_prefix_len(input, |c| c == '\'');
+        let body = &input[prefix_len..];
+        let len = if body.starts_with("'\\") {
+            // Escape sequence has variable length such as '\n', '\x41' or '\u{1F600}'
+            let len = escape_len(&body[1..]) + 1;
+            match body.chars().nth(len) {
+                Some('\'') => Some(len + 1),
+                _ => None,
+            }
+        } else {
+            let mut i = body.chars();
+            match (i.next(), i.next(), i.next()) {
+                (Some('\''), Some(_), Some('\'')) => Some(3),
+                _ => None,
+            }
+        };
+
+        if let Some(len) = len {
+            return Some(self.eat_n(out, input, Highlight::Char, prefix_len + len));
+        }
+
+        // Lifetime such as 'a or 'static in Rust. It is not closed by ' unlike character literal
+        if self.syntax.lifetime
+            && input.starts_with('\'')
+            && input[1..].starts_with(|c: char| c.is_alphabetic() || c == '_')
+        {
+            let len = input[1..].chars().take_while(|c| !is_sep(*c)).count() + 1;
+            let end = input[1..].find(is_sep).map_or(input.len(), |i| i + 1);
+            // Lifetime followed by ':' at statement start is a label such as `'outer: loop`
+            let hl = if self.syntax.statement_labels
+                && self.at_statement_start()
+                && input[end..].starts_with(':')
+                && !input[end..].starts_with("::")
+            {
+                Highlight::Label
+            } else {
+                Highlight::Lifetime
+            };
+            return Some(self.eat_n(out, input, hl, len));
+        }
+
+        None
+    }
+
+    fn highlight_regex(
+        &mut self,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        // '/' is a start of regular expression literal such as /ab+/g only where a value is expected.
+        // Otherwise it is a division like `a / b` or `f() / 2`
+        let (prev, prev_hl) = self.prev_token;
+        let is_value_pos = prev == '\0'
+            || "(,=:[!&|?{};+-*%<>~^".contains(prev)
+            || matches!(prev_hl, Highlight::Keyword | Highlight::Statement);
+        if c != '/' || !is_value_pos {
+            return None;
+        }
+
+        let (mut escaped, mut in_class) = (false, false);
+        let mut chars = input.chars().enumerate().skip(1);
+        let end = loop {
+            let (i, c) = chars.next()?; // Regular expression is not closed in this line
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '[' => in_class = true,
+                ']' => in_class = false,
+                '/' if !in_class => break i + 1,
+                _ => {}
+            }
+        };
+        let flags = input
+            .chars()
+            .skip(end)
+            .take_while(char::is_ascii_alphabetic)
+            .count();
+        Some(self.eat_n(out, input, Highlight::Regex, end + flags))
+    }
+
+    fn highlight_operator(&mut self, out: &mut [Highlight], input: &str) -> Option<ParseStep> {
+        // Longest operator is matched. e.g. '==' is not '=' followed by '='
+        let len = self
+            .syntax
+            .operators
+            .iter()
+            .filter(|op| input.starts_with(*op))
+            .map(|op| op.chars().count())
+            .max()?;
+        Some(self.eat_n(out, input, Highlight::Operator, len))
+    }
+
+    fn highlight_line_head_block_comment(
+        &mut self,
+        start: &str,
+        end: &str,
+        out: &mut [Highlight],
+        row: &str,
+    ) -> bool {
+        // Delimiter must be put at head of line and followed by whitespace. e.g. =begin in Ruby
+        fn starts_with_delim(row: &str, delim: &str) -> bool {
+            matches!(row.strip_prefix(delim), Some(r) if r.is_empty() || r.starts_with(char::is_whitespace))
+        }
+
+        if !self.state.in_line_head_block_comment && !starts_with_delim(row, start) {
+            return false;
+        }
+
+        self.state.in_line_head_block_comment = !starts_with_delim(row, end);
+        for hl in out.iter_mut() {
+            *hl = Highlight::Comment;
+        }
+        true
+    }
+
+    // Preprocessor directive such as #include in C. Returns number of characters highlighted
+    fn highlight_preprocessor(&mut self, out: &mut [Highlight], row: &str) -> usize {
+        if self.state.block_comment_depth > 0 || self.state.prev_quote.is_some() {
+            return 0;
+        }
+
+        // Directive must be the first token of line. Spaces are allowed around '#'
+        let body = row.trim_start();
+        let name = match body.strip_prefix('#') {
+            Some(rest) => rest.trim_start(),
+            None => return 0,
+        };
+        let name_len = name.find(is_sep).unwrap_or(name.len());
+        let char_idx = |byte_idx: usize| row[..byte_idx].chars().count();
+
+        let mut end = row.len() - name.len() + name_len;
+        for (i, hl) in out.iter_mut().take(char_idx(end)).enumerate() {
+            *hl = if i < char_idx(row.len() - body.len()) {
+                Highlight::Normal
+            } else {
+                Highlight::Preprocessor
+            };
+        }
+        self.prev_hl = Highlight::Preprocessor;
+
+        // Include target such as <stdio.h> is highlighted as string. "foo.h" is a normal string literal
+        if &name[..name_len] == "include" {
+            let target = row[end..].trim_start();
+            if let (true, Some(close)) = (target.starts_with('<'), target.find('>')) {
+                let start = row.len() - target.len();
+                end = start + close + 1;
+                for hl in out[char_idx(start)..char_idx(end)].iter_mut() {
+                    *hl = Highlight::String;
+                }
+                self.prev_hl = Highlight::String;
+            }
+        }
+
+        self.prev_char = row[..end].chars().next_back().unwrap();
+        char_idx(end)
+    }
+
+    // Key of mapping such as `key: value` or `- key: value` in YAML. Returns number of characters
+    // highlighted
+    fn highlight_mapping_key(&mut self, seps: &[char], out: &mut [Highlight], row: &str) -> usize {
+        if self.state.prev_quote.is_some() {
+            return 0;
+        }
+
+        let body = row.trim_start();
+        if self.syntax.table_headers {
+            let header = self.highlight_table_header(out, row, body);
+            if header > 0 {
+                return header;
+            }
+        }
+
+        let body = match body.strip_prefix('-') {
+            Some(rest) if rest.starts_with(' ') => rest.trim_start(), // Item of sequence
+            _ => body,
+        };
+        let is_comment = self
+            .syntax
+            .line_comments
+            .iter()
+            .any(|c| body.starts_with(c));
+        let key_chars = match body.chars().next() {
+            _ if is_comment => None,
+            Some(q) if self.syntax.string_kind(q).is_some() => key_len(body, q, seps),
+            None => None,
+            Some(_) => body
+                .char_indices()
+                .find(|(i, c)| seps.contains(c) && (*c != ':' || is_mapping_value(&body[i + 1..])))
+                .map(|(i, _)| body[..i].trim_end().chars().count())
+                .filter(|len| *len > 0),
+        };
+        let key_chars = match key_chars {
+            Some(len) => len,
+            None => return 0,
+        };
+
+        let indent = row[..row.len() - body.len()].chars().count();
+        for (i, hl) in out.iter_mut().take(indent + key_chars).enumerate() {
+            *hl = if i < indent {
+                Highlight::Normal
+            } else {
+                Highlight::Key
+            };
+        }
+        self.prev_hl = Highlight::Key;
+        self.prev_char = body.chars().nth(key_chars - 1).unwrap();
+        indent + key_chars
+    }
+
+    // Label such as `_start:` or `.L1:` at start of line in assembly. Returns the number of
+    // highlighted chars
+    fn highlight_label(&mut self, out: &mut [Highlight], row: &str) -> usize {
+        let body = row.trim_start();
+        let len = body
+            .find(|c: char| !c.is_alphanumeric() && !"_.$@".contains(c))
+            .unwrap_or(body.len());
+        if len == 0 || !body[len..].starts_with(':') {
+            return 0;
+        }
+        let indent = row[..row.len() - body.len()].chars().count();
+        let label = body[..len].chars().count();
+        for (i, hl) in out.iter_mut().take(indent + label + 1).enumerate() {
+            *hl = if indent <= i && i < indent + label {
+                Highlight::Label
+            } else {
+                Highlight::Normal
+            };
+        }
+        self.prev_hl = Highlight::Normal;
+        self.prev_char = ':';
+        self.prev_token = (':', Highlight::Normal);
+        indent + label + 1
+    }
+
+    // Target such as `all: deps` or `%.o: %.c` at start of line in Makefile. Returns the number of
+    // highlighted chars. Assignment such as `CC := gcc` is not a rule
+    fn highlight_make_target(&mut self, out: &mut [Highlight], row: &str) -> usize {
+        if self.state.prev_quote.is_some()
+            || row.starts_with(|c: char| c.is_whitespace() || c == '#')
+        {
+            return 0; // Recipe lines start with tab
+        }
+        let colon = match row.find(':') {
+            Some(i) => i,
+            None => return 0,
+        };
+        let target = row[..colon].trim_end();
+        if target.is_empty()
+            || target.contains('=')
+            || row[colon..].trim_start_matches(':').starts_with('=')
+        {
+            return 0;
+        }
+
+        let len = target.chars().count();
+        for hl in out.iter_mut().take(len) {
+            *hl = Highlight::Definition;
+        }
+        self.prev_hl = Highlight::Definition;
+        self.prev_char = target.chars().last().unwrap();
+        len
+    }
+
+    // Table header such as [package] or [[bin]] in TOML. Returns the number of highlighted chars
+    fn highlight_table_header(&mut self, out: &mut [Highlight], row: &str, body: &str) -> usize {
+        let close = if body.starts_with("[[") { "]]" } else { "]" };
+        let end = match (body.starts_with('['), body.find(close)) {
+            (true, Some(i)) => i + close.len(),
+            _ => return 0,
+        };
+        let indent = row[..row.len() - body.len()].chars().count();
+        let len = body[..end].chars().count();
+        for hl in out[indent..indent + len].iter_mut() {
+            *hl = Highlight::Keyword;
+        }
+        self.prev_hl = Highlight::Keyword;
+        self.prev_char = ']';
+        indent + len
+    }
+
+    fn highlight_anchor(
+        &mut self,
+        c: char,
+        out: &mut [Highlight],
+        input: &str,
+    ) -> Option<ParseStep> {
+        // Anchor &foo, alias *foo and tag !!str in YAML
+        let at_word_start = self.prev_char.is_whitespace() || self.prev_char == '\0';
+        if !matches!(c, '&' | '*' | '!') || !at_word_start {
+            return None;
+        }
+        let token = &input[..input.find(char::is_whitespace).unwrap_or(input.len())];
+        if token[1..].trim_start_matches('!').is_empty() {
+            return None;
+        }
+        let len = token.chars().count();
+        let hl = if c == '!' {
+            Highlight::Type
+        } else {
+            Highlight::Variable
+        };
+        Some(self.eat_n(out, input, hl, len))
+    }
+
+    fn highlight_markdown_line(&mut self, out: &mut [Highlight], row: &str) {
+        fn fill(out: &mut [Highlight], hl: Highlight) {
+            for out in out.iter_mut() {
+                *out = hl;
+            }
+        }
+
+        // Fenced code block is highlighted as string until closing fence
+        let trimmed = row.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            self.state.in_fenced_code = !self.state.in_fenced_code;
+            return fill(out, Highlight::Comment);
+        }
+        if self.state.in_fenced_code {
+            return fill(out, Highlight::String);
+        }
+
+        let level = trimmed.bytes().take_while(|b| *b == b'#').count();
+        let rest = &trimmed[level..];
+        if (1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(char::is_whitespace)) {
+            return fill(out, Highlight::Heading);
+        }
+
+        // Inline code `...` and emphasis such as **bold** or *italic*
+        let mut prev_char = '\0';
+        let mut iter = row.char_indices().enumerate();
+        while let Some((x, (idx, c))) = iter.next() {
+            let input = &row[idx..];
+            let span = match c {
+                '`' => input[1..]
+                    .find('`')
+                    .map(|end| (Highlight::String, input[..end + 2].chars().count())),
+                '*' | '_' => emphasis_len(input, prev_char).map(|len| (Highlight::Emphasis, len)),
+                _ => None,
+            };
+            match span {
+                Some((hl, len)) => {
+                    fill(&mut out[x..x + len], hl);
+                    prev_char = input.chars().nth(len - 1).unwrap();
+                    iter.nth(len.saturating_sub(2));
+                }
+                None => {
+                    out[x] = Highlight::Normal;
+                    prev_char = c;
+                }
+            }
+        }
+    }
+
+    fn highlight_one(&mut self, c: char, out: &mut [Highlight], input: &str) -> ParseStep {
+        if self.after_def_keyword && !c.is_ascii_whitespace() && is_sep(c) {
+            self.after_def_keyword = false;
+        }
+
+        macro_rules! try_highlight {
+            ($call:expr) => {
+                if let Some(step) = $call {
+                    return step;
+                }
+            };
+        }
+
+        let syntax = self.syntax;
+        if let Some((comment_start, comment_end)) = &syntax.block_comment {
+            try_highlight!(self.highlight_block_comment(comment_start, comment_end, c, out, input));
+        }
+
+        for comment_leader in syntax.line_comments.iter() {
+            try_highlight!(self.highlight_line_comment(comment_leader, out, input));
+        }
+
+        if let Some(leader) = self.syntax.statement_comment {
+            try_highlight!(self.highlight_statement_comment(leader, out, input));
+        }
+
+        if self.syntax.character {
+            try_highlight!(self.highlight_char(out, input));
+        }
+
+        if self.syntax.markup {
+            try_highlight!(self.highlight_markup(c, out, input));
+        }
+
+        if let Some(prefix) = self.syntax.line_strings {
+            try_highlight!(self.highlight_line_string(prefix, out, input));
+        }
+
+        // Quotes in text of markup language are not strings. e.g. "don't" in <p>don't</p>
+        let in_text = self.syntax.markup && !self.state.in_tag && self.state.prev_quote.is_none();
+        if !self.syntax.string_quotes.is_empty() && !in_text {
+            try_highlight!(self.highlight_string(c, out, input));
+        }
+
+        if self.syntax.sigils {
+            try_highlight!(self.highlight_sigil(c, out, input));
+        }
+
+        if !self.syntax.quote_operators.is_empty() {
+            try_highlight!(self.highlight_quote_operator(c, out, input));
+        }
+
+        if self.syntax.symbol {
+            try_highlight!(self.highlight_symbol(c, out, input));
+        }
+
+        if self.syntax.dollar_variables {
+            try_highlight!(self.highlight_variable(c, out, input));
+        }
+
+        if self.syntax.anchors {
+            try_highlight!(self.highlight_anchor(c, out, input));
+        }
+
+        if self.syntax.attribute {
+            try_highlight!(self.highlight_attribute(c, out, input));
+        }
+
+        if self.syntax.decorator {
+            try_highlight!(self.highlight_decorator(c, out, input));
+        }
+
+        if let Some(sigil) = self.syntax.annotation_sigil {
+            try_highlight!(self.highlight_annotation(sigil, c, out, input));
+        }
+
+        if self.syntax.at_builtins {
+            try_highlight!(self.highlight_at_name(Highlight::Builtin, c, out, input));
+        }
+
+        if self.syntax.at_macros {
+            try_highlight!(self.highlight_at_name(Highlight::Macro, c, out, input));
+        }
+
+        if self.syntax.hash_directives {
+            try_highlight!(self.highlight_hash_directive(c, out, input));
+        }
+
+        if self.syntax.dot_directives {
+            try_highlight!(self.highlight_dot_directive(c, out, input));
+        }
+
+        if !self.syntax.immediate_prefixes.is_empty() {
+            try_highlight!(self.highlight_immediate(c, out, input));
+        }
+
+        if self.syntax.hex_color {
+            try_highlight!(self.highlight_hex_color(c, out, input));
+        }
+
+        if self.syntax.property_names {
+            try_highlight!(self.highlight_property(Highlight::Keyword, out, input));
+        }
+
+        if self.syntax.field_names {
+            try_highlight!(self.highlight_property(Highlight::Key, out, input));
+        }
+
+        let is_bound = is_sep(self.prev_char) ^ is_sep(c);
+
+        if !self.syntax.variable_scopes.is_empty() && is_sep(self.prev_char) {
+            try_highlight!(self.highlight_scoped_variable(out, input));
+        }
+
+        // Highlight identifiers
+        if is_bound {
+            try_highlight!(self.highlight_ident(out, input));
+        }
+
+        if self.syntax.hex_number {
+            try_highlight!(self.highlight_prefix_number(NumLit::Hex, is_bound, c, out, input));
+        }
+
+        if self.syntax.bin_number {
+            try_highlight!(self.highlight_prefix_number(NumLit::Bin, is_bound, c, out, input));
+        }
+
+        if self.syntax.oct_number {
+            try_highlight!(self.highlight_prefix_number(NumLit::Oct, is_bound, c, out, input));
+        }
+
+        if self.syntax.number {
+            try_highlight!(self.highlight_digit_number(is_bound, c, out, input));
+        }
+
+        if self.syntax.regex_literal {
+            try_highlight!(self.highlight_regex(c, out, input));
+        }
+
+        if !self.syntax.operators.is_empty() {
+            try_highlight!(self.highlight_operator(out, input));
+        }
+
+        // Track nesting of { } to know whether in declaration block such as CSS
+        match c {
+            '{' => self.state.brace_depth += 1,
+            '}' => self.state.brace_depth = self.state.brace_depth.saturating_sub(1),
+            _ => {}
+        }
+
+        self.eat_one(out, c, Highlight::Normal)
+    }
+
+    fn highlight_line(&mut self, out: &mut [Highlight], row: &str) {
+        if self.syntax.lang == Language::Plain {
+            // On 'plain' syntax, skip highlighting since nothing is highlighted.
+            return;
+        }
+
+        if self.syntax.markdown {
+            // Markdown is not a programming language. It is highlighted line by line
+            return self.highlight_markdown_line(out, row);
+        }
+
+        // Initialize states for line highlighting
+        self.prev_hl = Highlight::Normal;
+        self.prev_char = '\0';
+        self.prev_token = ('\0', Highlight::Normal);
+        self.num = NumLit::Digit;
+        self.after_def_keyword = false;
+
+        if let Some((start, end)) = self.syntax.line_head_block_comment {
+            if self.highlight_line_head_block_comment(start, end, out, row) {
+                return;
+            }
+        }
+
+        let skip = if self.syntax.preprocessor {
+            self.highlight_preprocessor(out, row)
+        } else if let Some(seps) = self.syntax.mapping_keys {
+            self.highlight_mapping_key(seps, out, row)
+        } else if self.syntax.makefile {
+            self.highlight_make_target(out, row)
+        } else if self.syntax.labels {
+            self.highlight_label(out, row)
+        } else {
+            0
+        };
+
+        let mut iter = row.char_indices().enumerate().skip(skip);
+        while let Some((x, (idx, c))) = iter.next() {
+            let input = &row[idx..];
+            let out = &mut out[x..];
+            match self.highlight_one(c, out, input) {
+                ParseStep::Ahead(len) if len >= 2 => {
+                    // while statement always consume one character at top. Eat input chars considering that.
+                    iter.nth(len.saturating_sub(2));
+                }
+                ParseStep::Ahead(1) => { /* Go next */ }
+                ParseStep::Ahead(_) => unreachable!(),
+                ParseStep::Break => break,
+            }
+        }
+
+        // Unless the kind of string literal allows multiple lines, string literal continues to the
+        // next line only when newline is escaped with '\'
+        if let Some(Quote::Single(q) | Quote::Interpolated(q)) = self.state.prev_quote {
+            let continued = self.syntax.string_kind(q).is_some_and(|k| k.multiline)
+                || self.syntax.line_continuation && ends_with_line_continuation(row);
+            if !continued {
+                self.state.prev_quote = None;
+                self.state.raw_prefix = false;
+            }
+        }
+    }
+}
+
+pub struct RegionHighlight {
+    pub hl: Highlight,
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+}
+
+impl RegionHighlight {
+    fn contains(&self, (x, y): (usize, usize)) -> bool {
+        let ((sx, sy), (ex, ey)) = (self.start, self.end);
+        if y < sy || ey < y {
+            false
+        } else if sy < y && y < ey {
+            true
+        } else {
+            sx <= x && x < ex // Exclusive
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TrailingWhitespace {
+    Off,
+    // Highlight whitespaces at end of lines except for lines which only contain whitespaces
+    NonBlankLines,
+    AllLines,
+}
+
+fn highlight_trailing_whitespace(out: &mut [Highlight], row: &str, mode: TrailingWhitespace) {
+    if mode == TrailingWhitespace::Off {
+        return;
+    }
+    let start = match row.rfind(|c| c != ' ' && c != '\t') {
+        Some(idx) => row[..idx].chars().count() + 1,
+        None if mode == TrailingWhitespace::AllLines => 0,
+        None => return,
+    };
+    for hl in out[start..].iter_mut() {
+        *hl = Highlight::TrailingWhitespace;
+    }
+}
+
+// Lines scanned to find a matching bracket in each direction
+const MAX_BRACKET_SCAN_LINES: usize = 1000;
+
+// Run of the same highlight as (number of characters, highlight). u16 keeps one run in 4 bytes. Runs
+// longer than u16::MAX are split
+type HighlightRun = (u16, Highlight);
+
+// Compress highlights of characters into runs. Allocated buffer of `runs` is reused
+fn encode_runs(hls: &[Highlight], runs: &mut Vec<HighlightRun>) {
+    runs.clear();
+    for hl in hls.iter() {
+        match runs.last_mut() {
+            Some((len, prev)) if prev == hl && *len < u16::MAX => *len += 1,
+            _ => runs.push((1, *hl)),
+        }
+    }
+}
+
+fn decode_runs(runs: &[HighlightRun], hls: &mut Vec<Highlight>) {
+    hls.clear();
+    for (len, hl) in runs.iter() {
+        hls.extend(iter::repeat_n(*hl, *len as usize));
+    }
+}
+
+pub struct Highlighting {
+    pub needs_update: bool,
+    // Highlights of render text characters in each line as run-length encoding. Adjacent characters
+    // usually share the same highlight
+    lines: Vec<Vec<HighlightRun>>,
+    previous_bottom_of_screen: usize,
+    // States at end of lines which were already highlighted. Lines after them need to be highlighted
+    line_states: Vec<LineState>,
+    // Range of lines (inclusive) which were modified since previous update
+    dirty_lines: Option<(usize, usize)>,
+    matched: Vec<RegionHighlight>,
+    // Bracket under the cursor and its pair as (x, y, highlight under the bracket highlight)
+    brackets: Vec<(usize, usize, Highlight)>,
+    trailing_whitespace: TrailingWhitespace,
+    // When false, text is not highlighted though search matches and brackets still are
+    enabled: bool,
+    // Characters after this column are not highlighted to cap work on very long lines such as
+    // minified code
+    max_highlight_columns: Option<usize>,
+    syntax: Arc<SyntaxHighlight>,
+    keywords: KeywordTable,
+    color_scheme: ColorScheme,
+}
+
+impl Default for Highlighting {
+    fn default() -> Self {
+        Highlighting {
+            needs_update: false,
+            lines: vec![],
+            previous_bottom_of_screen: 0,
+            line_states: vec![],
+            dirty_lines: None,
+            matched: vec![],
+            brackets: vec![],
+            trailing_whitespace: TrailingWhitespace::Off,
+            enabled: true,
+            max_highlight_columns: None,
+            syntax: Arc::new(PLAIN_SYNTAX),
+            keywords: KeywordTable::new(&PLAIN_SYNTAX),
+            color_scheme: ColorScheme::default(),
+        }
+    }
+}
+
+impl Highlighting {
+    pub fn new(lang: Language, rows: &[Row]) -> Highlighting {
+        Highlighting {
+            needs_update: true,
+            lines: rows
+                .iter()
+                .map(|r| {
+                    let (len, max) = (r.render_text().chars().count(), u16::MAX as usize);
+                    (0..len)
+                        .step_by(max)
+                        .map(|i| (cmp::min(len - i, max) as u16, Highlight::Normal))
+                        .collect()
+                })
+                .collect(),
+            previous_bottom_of_screen: 0,
+            line_states: vec![],
+            dirty_lines: None,
+            matched: vec![],
+            brackets: vec![],
+            trailing_whitespace: TrailingWhitespace::Off,
+            enabled: true,
+            max_highlight_columns: None,
+            syntax: SyntaxHighlight::for_lang(lang),
+            keywords: KeywordTable::new(&SyntaxHighlight::for_lang(lang)),
+            color_scheme: ColorScheme::default(),
+        }
+    }
+
+    pub fn set_color_scheme(&mut self, scheme: ColorScheme) {
+        self.color_scheme = scheme;
+    }
+
+    pub fn color_scheme(&self) -> &ColorScheme {
+        &self.color_scheme
+    }
+
+    // Highlight of the character at (x, y) in render text. None when the position is out of range
+    pub fn highlight_at(&self, y: usize, x: usize) -> Option<Highlight> {
+        self.spans(y)
+            .find(|(_, end, _)| x < *end)
+            .map(|(_, _, hl)| hl)
+    }
+
+    // Runs of the same highlight in line `y` as (start, end, highlight). `end` is exclusive
+    pub fn spans(&self, y: usize) -> impl Iterator<Item = (usize, usize, Highlight)> + '_ {
+        let line = self.lines.get(y).map(Vec::as_slice).unwrap_or(&[]);
+        line.iter().scan(0, |start, (len, hl)| {
+            let span = (*start, *start + *len as usize, *hl);
+            *start = span.1;
+            Some(span)
+        })
+    }
+
+    // Highlight of each character in line `y`
+    pub fn line(&self, y: usize) -> impl Iterator<Item = Highlight> + '_ {
+        self.spans(y)
+            .flat_map(|(start, end, hl)| iter::repeat_n(hl, end - start))
+    }
+
+    pub fn set_trailing_whitespace(&mut self, trailing_whitespace: TrailingWhitespace) {
+        if self.trailing_whitespace == trailing_whitespace {
+            return;
+        }
+        self.trailing_whitespace = trailing_whitespace;
+        self.line_states.clear(); // Highlight all lines again
+        self.needs_update = true;
+    }
+
+    // Note that states at end of a truncated line such as an unterminated string are carried to
+    // the next line as they are
+    pub fn set_max_highlight_columns(&mut self, max: Option<usize>) {
+        if self.max_highlight_columns == max {
+            return;
+        }
+        self.max_highlight_columns = max;
+        self.line_states.clear(); // Highlight all lines again
+        self.needs_update = true;
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if self.enabled == enabled {
+            return;
+        }
+        self.enabled = enabled;
+        self.line_states.clear(); // Highlight all lines again
+        self.needs_update = true;
+    }
+
+    // Highlight all lines again from the first line on next update. This is useful when highlight
+    // depends on something other than text such as a color scheme or a loaded syntax file
+    pub fn force_refresh(&mut self) {
+        self.line_states.clear(); // Cached states are not reused
+        self.dirty_lines = None;
+        self.previous_bottom_of_screen = 0;
+        self.needs_update = true;
+    }
+
+    // Register syntax built with `SyntaxHighlightBuilder`. Files with the extensions are detected
+    // as the returned language
+    pub fn register_syntax(syntax: SyntaxHighlight, file_exts: &[&str]) -> Language {
+        let file_exts = file_exts.iter().map(|e| e.to_string()).collect();
+        syntax_file::register_syntax(syntax, file_exts)
+    }
+
+    pub fn lang_changed(&mut self, new_lang: Language) {
+        if self.syntax.lang == new_lang {
+            return;
+        }
+        self.syntax = SyntaxHighlight::for_lang(new_lang);
+        self.keywords = KeywordTable::new(&self.syntax);
+        // Matches were found in the old content. Clear them not to restore stale match highlights
+        self.clear_previous_match();
+        self.line_states.clear();
+        self.dirty_lines = None;
+        self.needs_update = true;
+    }
+
+    // Mark the line as modified. Lines after it are also highlighted again while the edit changes
+    // states at end of line such as an unterminated block comment
+    pub fn invalidate_line(&mut self, y: usize) {
+        self.dirty_lines = Some(match self.dirty_lines {
+            Some((start, end)) => (cmp::min(start, y), cmp::max(end, y)),
+            None => (y, y),
+        });
+        self.needs_update = true;
+    }
+
+    // Overwrite highlight at (x, y) and return the previous one
+    fn paint(&mut self, y: usize, x: usize, highlight: Highlight) -> Option<Highlight> {
+        let mut hls = vec![];
+        let line = self.lines.get_mut(y)?;
+        decode_runs(line, &mut hls);
+        let prev = hls.get_mut(x).map(|hl| mem::replace(hl, highlight));
+        encode_runs(&hls, line);
+        prev
+    }
+
+    fn highlight_brackets(&mut self) {
+        for i in 0..self.brackets.len() {
+            let (x, y, _) = self.brackets[i];
+            match self.paint(y, x, Highlight::Bracket) {
+                // Remember highlight under bracket only when the line was highlighted again
+                Some(prev) if prev != Highlight::Bracket => self.brackets[i].2 = prev,
+                _ => {}
+            }
+        }
+    }
+
+    fn find_matching_bracket(&self, rows: &[Row], cx: usize, cy: usize) -> Option<(usize, usize)> {
+        let in_code = |x, y| {
+            !matches!(
+                self.highlight_at(y, x),
+                Some(
+                    Highlight::String
+                        | Highlight::Char
+                        | Highlight::Comment
+                        | Highlight::DocComment
+                )
+            )
+        };
+
+        // Positions are indices in render text to look up highlights
+        let bracket = rows.get(cy)?.render_text().chars().nth(cx)?;
+        let (pair, forward) = match bracket {
+            '(' => (')', true),
+            '[' => (']', true),
+            '{' => ('}', true),
+            ')' => ('(', false),
+            ']' => ('[', false),
+            '}' => ('{', false),
+            _ => return None,
+        };
+        if !in_code(cx, cy) {
+            return None;
+        }
 
-        for out in out.iter_mut().take(len) {
-            *out = hl;
+        let mut depth = 0;
+        let mut visit = |x, y, c| {
+            if c == bracket && in_code(x, y) {
+                depth += 1;
+            } else if c == pair && in_code(x, y) {
+                depth -= 1;
+                return depth == 0;
+            }
+            false
+        };
+
+        if forward {
+            let end = cmp::min(rows.len(), cy + MAX_BRACKET_SCAN_LINES);
+            for (y, row) in rows.iter().enumerate().take(end).skip(cy) {
+                let start = if y == cy { cx } else { 0 };
+                for (x, c) in row.render_text().chars().enumerate().skip(start) {
+                    if visit(x, y, c) {
+                        return Some((x, y));
+                    }
+                }
+            }
+        } else {
+            let start = cy.saturating_sub(MAX_BRACKET_SCAN_LINES - 1);
+            for y in (start..=cy).rev() {
+                let chars: Vec<_> = rows[y].render_text().chars().collect();
+                let end = if y == cy { cx + 1 } else { chars.len() };
+                for x in (0..end).rev() {
+                    if visit(x, y, chars[x]) {
+                        return Some((x, y));
+                    }
+                }
+            }
         }
-        self.prev_hl = hl;
-        self.prev_char = input.chars().nth(len - 1).unwrap();
-        ParseStep::Ahead(len)
+        None
     }
 
-    fn eat_one(&mut self, out: &mut [Highlight], c: char, hl: Highlight) -> ParseStep {
-        out[0] = hl;
-        self.prev_hl = hl;
-        self.prev_char = c;
-        ParseStep::Ahead(1)
+    // Highlight the bracket at the cursor and its pair. Previous bracket highlights are restored.
+    // Returns the first line whose highlights were changed
+    pub fn highlight_matching_bracket(
+        &mut self,
+        rows: &[Row],
+        cx: usize,
+        cy: usize,
+    ) -> Option<usize> {
+        let cx = rows.get(cy).map(|r| r.render_idx_of(cx)).unwrap_or(cx);
+        let mut changed = None;
+        for (x, y, saved) in mem::take(&mut self.brackets) {
+            self.paint(y, x, saved);
+            changed = cmp::min(changed.or(Some(y)), Some(y));
+        }
+
+        if let Some((x, y)) = self.find_matching_bracket(rows, cx, cy) {
+            for &(x, y) in &[(cx, cy), (x, y)] {
+                if let Some(hl) = self.highlight_at(y, x) {
+                    self.brackets.push((x, y, hl));
+                    changed = cmp::min(changed.or(Some(y)), Some(y));
+                }
+            }
+            self.highlight_brackets();
+        }
+
+        changed
     }
 
-    fn highlight_block_comment(
-        &mut self,
-        start: &str,
-        end: &str,
-        c: char,
-        out: &mut [Highlight],
-        input: &str,
-    ) -> Option<ParseStep> {
-        if self.prev_quote.is_some() {
-            return None;
+    fn highlight_match(&mut self, rows: &[Row]) {
+        // Matched region is at character positions in buffer. Convert them to positions in render
+        // text where tabs were expanded
+        let render_idx = |(x, y): (usize, usize)| {
+            let x = rows.get(y).map(|r| r.render_idx_of(x)).unwrap_or(x);
+            (x, y)
+        };
+        let mut hls = vec![];
+        for region in self.matched.iter() {
+            let region = RegionHighlight {
+                hl: region.hl,
+                start: render_idx(region.start),
+                end: render_idx(region.end),
+            };
+            // Matched region may be stale after lines were removed. Ignore lines out of range
+            let end = cmp::min(region.end.1 + 1, self.lines.len());
+            for y in region.start.1..end {
+                let line = &mut self.lines[y];
+                decode_runs(line, &mut hls);
+                for (x, hl) in hls.iter_mut().enumerate() {
+                    if region.contains((x, y)) {
+                        *hl = region.hl;
+                    }
+                }
+                encode_runs(&hls, line);
+            }
         }
+    }
 
-        let comment_delim = if self.in_block_comment && input.starts_with(end) {
-            self.in_block_comment = false;
-            end
-        } else if !self.in_block_comment && input.starts_with(start) {
-            self.in_block_comment = true;
-            start
-        } else {
-            return if self.in_block_comment {
-                Some(self.eat_one(out, c, Highlight::Comment))
+    pub fn update(&mut self, rows: &[Row], bottom_of_screen: usize) {
+        if !self.needs_update && bottom_of_screen <= self.previous_bottom_of_screen {
+            return;
+        }
+
+        let (dirty_start, dirty_end) = self.dirty_lines.take().unwrap_or((usize::MAX, 0));
+        if self.lines.len() != rows.len() {
+            // When lines were inserted or removed, cached states after the edit no longer correspond
+            // to rows
+            self.line_states.truncate(dirty_start);
+        }
+
+        // Lines before `start` were already highlighted and not edited after that. Restart
+        // highlighting with the state at end of the previous line
+        let start = cmp::min(dirty_start, self.line_states.len());
+        let state = match start {
+            0 => LineState::default(),
+            _ => self.line_states[start - 1],
+        };
+        let mut highlighter = Highlighter::new(&self.syntax, &self.keywords, state);
+
+        self.lines.resize_with(rows.len(), Default::default);
+        let (mut hls, mut runs) = (vec![], vec![]);
+        for (y, row) in rows.iter().enumerate().take(bottom_of_screen).skip(start) {
+            let row = row.render_text();
+            hls.clear();
+            hls.resize(row.chars().count(), Highlight::Normal);
+
+            if self.enabled {
+                let (out, text) = match self.max_highlight_columns {
+                    Some(max) if hls.len() > max => {
+                        let end = row.char_indices().nth(max).unwrap().0;
+                        (&mut hls[..max], &row[..end])
+                    }
+                    _ => (&mut hls[..], row),
+                };
+                highlighter.highlight_line(out, text);
+                highlight_todo_markers(out, text);
+                highlight_links(out, text);
+                highlight_trailing_whitespace(&mut hls, row, self.trailing_whitespace);
+            }
+            encode_runs(&hls, &mut runs);
+            // Most lines are highlighted in the same way as before. Keep the buffer untouched
+            let line = &mut self.lines[y];
+            if *line != runs {
+                line.clear();
+                line.extend_from_slice(&runs);
+            }
+
+            let state = highlighter.state;
+            if let Some(cached) = self.line_states.get_mut(y) {
+                let unchanged = *cached == state;
+                *cached = state;
+                if unchanged && y >= dirty_end {
+                    // Following lines are not affected by the edit since they start with the same state
+                    break;
+                }
+                if y + 1 == bottom_of_screen {
+                    // Highlighting stops before the change settles. States cached for lines below the
+                    // screen are stale and must be computed again when scrolling down
+                    self.line_states.truncate(y + 1);
+                }
             } else {
-                None
-            };
+                self.line_states.push(state);
+            }
+        }
+
+        // Overwrite matched region
+        //
+        // TODO: Move logic to highlighter rather than overwriting highlights after.
+        // Give self.matched to Highlighter::new() and it checks each cell should be highlighted as match
+        self.highlight_match(rows);
+        self.highlight_brackets();
+
+        self.needs_update = false;
+        self.previous_bottom_of_screen = bottom_of_screen;
+    }
+
+    pub fn set_matches(&mut self, matches: Vec<RegionHighlight>) {
+        self.clear_previous_match();
+        self.matched = matches;
+    }
+
+    pub fn clear_previous_match(&mut self) -> Option<usize> {
+        let dirty_start = self.matched.iter().map(|r| r.start.1).min();
+        let dirty_end = self.matched.iter().map(|r| r.end.1).max();
+        if let (Some(start), Some(end)) = (dirty_start, dirty_end) {
+            self.matched.clear();
+            // Highlight matched lines again to remove match highlights
+            for y in start..=end {
+                self.invalidate_line(y);
+            }
+        }
+        dirty_start
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ops::Range;
+
+    fn highlight(lang: Language, lines: &[&str]) -> Highlighting {
+        let rows: Vec<_> = lines.iter().map(|l| Row::new(*l).unwrap()).collect();
+        let mut hl = Highlighting::new(lang, &rows);
+        hl.update(&rows, rows.len());
+        hl
+    }
+
+    fn set_highlight(hl: &mut Highlighting, y: usize, x: usize, highlight: Highlight) {
+        let mut hls = vec![];
+        decode_runs(&hl.lines[y], &mut hls);
+        hls[x] = highlight;
+        encode_runs(&hls, &mut hl.lines[y]);
+    }
+
+    fn assert_hl(hl: &Highlighting, y: usize, xs: Range<usize>, expected: Highlight) {
+        for x in xs {
+            assert_eq!(
+                hl.highlight_at(y, x).unwrap(),
+                expected,
+                "at x={} y={}: {:?}",
+                x,
+                y,
+                hl.lines[y]
+            );
+        }
+    }
+
+    #[test]
+    fn python_triple_quoted_string() {
+        let hl = highlight(
+            Language::Python,
+            &[
+                "def f():",
+                "    \"\"\"Docstring",
+                "    'quoted' and \"quoted\"",
+                "    def not_keyword",
+                "    \"\"\"",
+                "    return ''' x '''",
+            ],
+        );
+        assert_hl(&hl, 1, 4..16, Highlight::String);
+        assert_hl(&hl, 2, 0..25, Highlight::String);
+        assert_hl(&hl, 3, 0..19, Highlight::String);
+        assert_hl(&hl, 4, 4..7, Highlight::String);
+        assert_hl(&hl, 5, 4..10, Highlight::Statement);
+        assert_hl(&hl, 5, 11..20, Highlight::String);
+    }
+
+    #[test]
+    fn python_string_prefixes() {
+        let hl = highlight(
+            Language::Python,
+            &[
+                "x = f\"hi\" + elif\"\"",
+                "F'''a'''",
+                "rb\"\\x00\" + Rb'a' + u'b'",
+                "br = ur\"\" + rb",
+                "r'\\'' + R\"\"\"\\n\"\"\"",
+            ],
+        );
+        assert_hl(&hl, 0, 0..4, Highlight::Normal);
+        assert_hl(&hl, 0, 4..9, Highlight::String);
+        assert_hl(&hl, 0, 12..16, Highlight::Statement);
+        assert_hl(&hl, 0, 16..18, Highlight::String);
+        assert_hl(&hl, 1, 0..8, Highlight::String);
+        // No escape in raw string
+        assert_hl(&hl, 2, 0..8, Highlight::String);
+        assert_hl(&hl, 2, 8..11, Highlight::Normal);
+        assert_hl(&hl, 2, 11..16, Highlight::String);
+        assert_hl(&hl, 2, 19..23, Highlight::String);
+        // 'ur' is not a valid prefix
+        assert_hl(&hl, 3, 0..7, Highlight::Normal);
+        assert_hl(&hl, 3, 7..9, Highlight::String);
+        assert_hl(&hl, 3, 9..14, Highlight::Normal);
+        assert_hl(&hl, 4, 0..5, Highlight::String);
+        assert_hl(&hl, 4, 5..8, Highlight::Normal);
+        assert_hl(&hl, 4, 8..17, Highlight::String);
+    }
+
+    #[test]
+    fn ruby_line_head_block_comment() {
+        let hl = highlight(
+            Language::Ruby,
+            &[
+                "=begin",
+                "def foo",
+                " =end",
+                "=end",
+                "def foo",
+                "=beginning",
+            ],
+        );
+        assert_hl(&hl, 0, 0..6, Highlight::Comment);
+        assert_hl(&hl, 1, 0..7, Highlight::Comment);
+        assert_hl(&hl, 2, 0..5, Highlight::Comment);
+        assert_hl(&hl, 3, 0..4, Highlight::Comment);
+        assert_hl(&hl, 4, 0..3, Highlight::Keyword);
+        assert_hl(&hl, 5, 0..10, Highlight::Normal);
+    }
+
+    #[test]
+    fn ruby_symbol() {
+        let hl = highlight(Language::Ruby, &["f(:foo, Foo::Bar, a ? b : c)"]);
+        assert_hl(&hl, 0, 2..6, Highlight::Symbol);
+        assert_hl(&hl, 0, 6..27, Highlight::Normal);
+    }
+
+    #[test]
+    fn typescript_keywords_and_decorator() {
+        let hl = highlight(
+            Language::TypeScript,
+            &["@Component", "interface Foo { s: string; }", "x@y"],
+        );
+        assert_hl(&hl, 0, 0..10, Highlight::Annotation);
+        assert_hl(&hl, 1, 0..9, Highlight::Keyword);
+        assert_hl(&hl, 1, 10..13, Highlight::Definition);
+        assert_hl(&hl, 1, 19..25, Highlight::Type);
+        assert_hl(&hl, 2, 0..3, Highlight::Normal);
+    }
+
+    #[test]
+    fn rust_usize_type() {
+        let hl = highlight(Language::Rust, &["let x: usize = 0;"]);
+        assert_hl(&hl, 0, 7..12, Highlight::Type);
+    }
+
+    #[test]
+    fn javascript_reflect_type() {
+        let hl = highlight(Language::JavaScript, &["Reflect.ownKeys(o);"]);
+        assert_hl(&hl, 0, 0..7, Highlight::Type);
+        assert_hl(&hl, 0, 7..8, Highlight::Normal);
+    }
+
+    #[test]
+    fn escaped_backslash_in_string() {
+        let hl = highlight(
+            Language::C,
+            &[
+                r#""a\\" int"#,
+                r#""\\\\" int"#,
+                r#""\"" int"#,
+                r#""\\"" int"#,
+            ],
+        );
+        assert_hl(&hl, 0, 0..2, Highlight::String);
+        assert_hl(&hl, 0, 2..4, Highlight::Escape);
+        assert_hl(&hl, 0, 4..5, Highlight::String);
+        assert_hl(&hl, 0, 6..9, Highlight::Type);
+        assert_hl(&hl, 1, 1..5, Highlight::Escape);
+        assert_hl(&hl, 1, 5..6, Highlight::String);
+        assert_hl(&hl, 1, 7..10, Highlight::Type);
+        assert_hl(&hl, 2, 1..3, Highlight::Escape);
+        assert_hl(&hl, 2, 3..4, Highlight::String);
+        assert_hl(&hl, 2, 5..8, Highlight::Type);
+        // "\\" is closed and then next " opens a new string literal
+        assert_hl(&hl, 3, 1..3, Highlight::Escape);
+        assert_hl(&hl, 3, 3..9, Highlight::String);
+    }
+
+    #[test]
+    fn rust_raw_string() {
+        let hl = highlight(
+            Language::Rust,
+            &[
+                r#"r"no escapes \" as"#,
+                r###"r#"has "quotes""# as"###,
+                r###"r##"a"#b"## as"###,
+                r###"r#"multi"###,
+                r###"line"# as"###,
+            ],
+        );
+        assert_hl(&hl, 0, 0..15, Highlight::String);
+        assert_hl(&hl, 0, 16..18, Highlight::Keyword);
+        assert_hl(&hl, 1, 0..17, Highlight::String);
+        assert_hl(&hl, 1, 18..20, Highlight::Keyword);
+        assert_hl(&hl, 2, 0..11, Highlight::String);
+        assert_hl(&hl, 2, 12..14, Highlight::Keyword);
+        assert_hl(&hl, 3, 0..8, Highlight::String);
+        assert_hl(&hl, 4, 0..6, Highlight::String);
+        assert_hl(&hl, 4, 7..9, Highlight::Keyword);
+    }
+
+    #[test]
+    fn rust_byte_literals() {
+        let hl = highlight(
+            Language::Rust,
+            &[r##"b"bytes" b'\n' b'a' br#"x"# as"##, r#"numb"x" as"#],
+        );
+        assert_hl(&hl, 0, 0..8, Highlight::String);
+        assert_hl(&hl, 0, 9..14, Highlight::Char);
+        assert_hl(&hl, 0, 15..19, Highlight::Char);
+        assert_hl(&hl, 0, 20..27, Highlight::String);
+        assert_hl(&hl, 0, 28..30, Highlight::Keyword);
+        assert_hl(&hl, 1, 0..4, Highlight::Normal);
+        assert_hl(&hl, 1, 4..7, Highlight::String);
+        assert_hl(&hl, 1, 8..10, Highlight::Keyword);
+    }
+
+    #[test]
+    fn nested_block_comment() {
+        let src = &["/* a /* b */ c */ d", "/* /*", "*/ */ d"];
+
+        let hl = highlight(Language::Rust, src);
+        assert_hl(&hl, 0, 0..17, Highlight::Comment);
+        assert_hl(&hl, 0, 17..19, Highlight::Normal);
+        assert_hl(&hl, 1, 0..5, Highlight::Comment);
+        assert_hl(&hl, 2, 0..5, Highlight::Comment);
+        assert_hl(&hl, 2, 5..7, Highlight::Normal);
+
+        let hl = highlight(Language::C, src);
+        assert_hl(&hl, 0, 0..12, Highlight::Comment);
+        assert_hl(&hl, 0, 12..15, Highlight::Normal);
+        assert_hl(&hl, 2, 0..2, Highlight::Comment);
+        assert_hl(&hl, 2, 3..5, Highlight::Operator);
+    }
+
+    #[test]
+    fn multibyte_identifier() {
+        let hl = highlight(Language::Rust, &["let 名前 = 1; if", "/* 😀 */ fn"]);
+        assert_hl(&hl, 0, 4..6, Highlight::Definition);
+        assert_hl(&hl, 0, 7..8, Highlight::Operator);
+        assert_hl(&hl, 0, 9..10, Highlight::Number);
+        assert_hl(&hl, 0, 12..14, Highlight::Statement);
+        assert_hl(&hl, 1, 0..7, Highlight::Comment);
+        assert_hl(&hl, 1, 8..10, Highlight::Keyword);
+    }
+
+    #[test]
+    fn prefixed_number_literals() {
+        let hl = highlight(Language::Rust, &["0xDeadBeef 0b_1010 0o777 0o8 0xyz"]);
+        assert_hl(&hl, 0, 0..10, Highlight::Number);
+        assert_hl(&hl, 0, 11..18, Highlight::Number);
+        assert_hl(&hl, 0, 19..24, Highlight::Number);
+        assert_hl(&hl, 0, 25..26, Highlight::Number);
+        assert_hl(&hl, 0, 26..28, Highlight::Normal);
+        assert_hl(&hl, 0, 29..30, Highlight::Number);
+        assert_hl(&hl, 0, 30..33, Highlight::Normal);
+    }
+
+    #[test]
+    fn number_separator_exponent_suffix() {
+        let hl = highlight(
+            Language::Rust,
+            &[
+                "1_000_000 1.5e-10 1i32 3.14f64 2E3",
+                "1..10 x_1 1.max(2) 1. 1ix",
+            ],
+        );
+        assert_hl(&hl, 0, 0..9, Highlight::Number);
+        assert_hl(&hl, 0, 10..17, Highlight::Number);
+        assert_hl(&hl, 0, 18..22, Highlight::Number);
+        assert_hl(&hl, 0, 23..30, Highlight::Number);
+        assert_hl(&hl, 0, 31..34, Highlight::Number);
+        assert_hl(&hl, 1, 0..1, Highlight::Number);
+        assert_hl(&hl, 1, 1..3, Highlight::Operator);
+        assert_hl(&hl, 1, 3..5, Highlight::Number);
+        assert_hl(&hl, 1, 6..9, Highlight::Normal);
+        assert_hl(&hl, 1, 10..11, Highlight::Number);
+        assert_hl(&hl, 1, 11..12, Highlight::Normal);
+        assert_hl(&hl, 1, 12..15, Highlight::Function);
+        assert_hl(&hl, 1, 19..21, Highlight::Number);
+        assert_hl(&hl, 1, 22..23, Highlight::Number);
+        assert_hl(&hl, 1, 23..25, Highlight::Normal);
+    }
+
+    #[test]
+    fn function_name() {
+        let hl = highlight(Language::C, &["foo(1); if (x) bar (y); baz = 1; 1 (2)"]);
+        assert_hl(&hl, 0, 0..3, Highlight::Function);
+        assert_hl(&hl, 0, 8..10, Highlight::Statement);
+        assert_hl(&hl, 0, 15..18, Highlight::Function);
+        assert_hl(&hl, 0, 24..27, Highlight::Normal);
+        assert_hl(&hl, 0, 33..34, Highlight::Number);
+
+        let hl = highlight(Language::Rust, &["fn foo() { Some(x) }"]);
+        assert_hl(&hl, 0, 3..6, Highlight::Definition);
+        assert_hl(&hl, 0, 11..15, Highlight::Type);
+    }
+
+    #[test]
+    fn rust_macro() {
+        let hl = highlight(Language::Rust, &[r#"println!("x"); a != b; !flag; a!=b"#]);
+        assert_hl(&hl, 0, 0..8, Highlight::Macro);
+        assert_hl(&hl, 0, 17..19, Highlight::Operator);
+        assert_hl(&hl, 0, 23..24, Highlight::Operator);
+        assert_hl(&hl, 0, 24..28, Highlight::Normal);
+        assert_hl(&hl, 0, 31..33, Highlight::Operator);
+    }
+
+    #[test]
+    fn rust_attribute() {
+        let hl = highlight(
+            Language::Rust,
+            &[
+                "#[derive(Clone, Copy)] struct",
+                "#![cfg(any(a, b))]",
+                "# [x",
+                "#[cfg(foo",
+            ],
+        );
+        assert_hl(&hl, 0, 0..22, Highlight::Attribute);
+        assert_hl(&hl, 0, 23..29, Highlight::Keyword);
+        assert_hl(&hl, 1, 0..18, Highlight::Attribute);
+        assert_hl(&hl, 2, 0..4, Highlight::Normal);
+        assert_hl(&hl, 3, 0..9, Highlight::Attribute);
+    }
+
+    #[test]
+    fn rust_lifetime() {
+        let hl = highlight(
+            Language::Rust,
+            &["fn f<'a>(x: &'a str) -> &'static str", "let c = 'a'; '_"],
+        );
+        assert_hl(&hl, 0, 5..7, Highlight::Lifetime);
+        assert_hl(&hl, 0, 7..8, Highlight::Operator);
+        assert_hl(&hl, 0, 8..10, Highlight::Normal);
+        assert_hl(&hl, 0, 13..15, Highlight::Lifetime);
+        assert_hl(&hl, 0, 25..32, Highlight::Lifetime);
+        assert_hl(&hl, 1, 8..11, Highlight::Char);
+        assert_hl(&hl, 1, 13..15, Highlight::Lifetime);
+    }
+
+    #[test]
+    fn c_preprocessor() {
+        let hl = highlight(
+            Language::C,
+            &[
+                "#include <x.h>",
+                "  # define FOO 1",
+                "int x; # y",
+                "#include \"y.h\"",
+            ],
+        );
+        assert_hl(&hl, 0, 0..8, Highlight::Preprocessor);
+        assert_hl(&hl, 0, 8..9, Highlight::Normal);
+        assert_hl(&hl, 0, 9..14, Highlight::String);
+        assert_hl(&hl, 1, 0..2, Highlight::Normal);
+        assert_hl(&hl, 1, 2..10, Highlight::Preprocessor);
+        assert_hl(&hl, 1, 11..14, Highlight::Constant);
+        assert_hl(&hl, 1, 15..16, Highlight::Number);
+        assert_hl(&hl, 2, 3..10, Highlight::Normal);
+        assert_hl(&hl, 3, 0..8, Highlight::Preprocessor);
+        assert_hl(&hl, 3, 9..14, Highlight::String);
+    }
+
+    #[test]
+    fn todo_marker_in_comment() {
+        let hl = highlight(
+            Language::Rust,
+            &[
+                "// TODO: fix this",
+                "// TODOLIST FIXME",
+                "let TODO = 1; /* XXX */",
+            ],
+        );
+        assert_hl(&hl, 0, 0..3, Highlight::Comment);
+        assert_hl(&hl, 0, 3..7, Highlight::Todo);
+        assert_hl(&hl, 0, 7..17, Highlight::Comment);
+        assert_hl(&hl, 1, 0..12, Highlight::Comment);
+        assert_hl(&hl, 1, 12..17, Highlight::Todo);
+        assert_hl(&hl, 2, 4..8, Highlight::Definition);
+        assert_hl(&hl, 2, 17..20, Highlight::Todo);
+    }
+
+    #[test]
+    fn rust_doc_comment() {
+        let hl = highlight(
+            Language::Rust,
+            &[
+                "/// docs",
+                "// note",
+                "//! crate docs",
+                "//// not docs",
+                "/** doc",
+                "block */ x /**/ y",
+            ],
+        );
+        assert_hl(&hl, 0, 0..8, Highlight::DocComment);
+        assert_hl(&hl, 1, 0..7, Highlight::Comment);
+        assert_hl(&hl, 2, 0..14, Highlight::DocComment);
+        assert_hl(&hl, 3, 0..13, Highlight::Comment);
+        assert_hl(&hl, 4, 0..7, Highlight::DocComment);
+        assert_hl(&hl, 5, 0..8, Highlight::DocComment);
+        assert_hl(&hl, 5, 9..10, Highlight::Normal);
+        assert_hl(&hl, 5, 11..15, Highlight::Comment);
+        assert_hl(&hl, 5, 16..17, Highlight::Normal);
+    }
+
+    #[test]
+    fn escape_sequence_in_string() {
+        let hl = highlight(
+            Language::Rust,
+            &[
+                r#""a\nb" "\x41" "\\" "\u{1F600}!""#,
+                r#"r"\n" 1"#,
+                r#"let s = "\u{"; let t = {};"#,
+            ],
+        );
+        assert_hl(&hl, 0, 0..2, Highlight::String);
+        assert_hl(&hl, 0, 2..4, Highlight::Escape);
+        assert_hl(&hl, 0, 4..6, Highlight::String);
+        assert_hl(&hl, 0, 8..12, Highlight::Escape);
+        assert_hl(&hl, 0, 12..13, Highlight::String);
+        assert_hl(&hl, 0, 15..17, Highlight::Escape);
+        assert_hl(&hl, 0, 17..18, Highlight::String);
+        assert_hl(&hl, 0, 20..29, Highlight::Escape);
+        assert_hl(&hl, 0, 29..31, Highlight::String);
+        assert_hl(&hl, 1, 0..5, Highlight::String);
+        assert_hl(&hl, 1, 6..7, Highlight::Number);
+        assert_hl(&hl, 2, 8..9, Highlight::String);
+        assert_hl(&hl, 2, 9..11, Highlight::Escape);
+        assert_hl(&hl, 2, 11..13, Highlight::String);
+        assert_hl(&hl, 2, 13..15, Highlight::Normal);
+        assert_hl(&hl, 2, 15..18, Highlight::Keyword);
+        assert_hl(&hl, 2, 23..26, Highlight::Normal);
+    }
+
+    #[test]
+    fn css_rule() {
+        let hl = highlight(
+            Language::Css,
+            &[
+                "a:hover, #fff {",
+                "  color: #ff0000;",
+                "  font-size: 1.5em; width: 50%;",
+                "}",
+                "@media screen { }",
+            ],
+        );
+        assert_hl(&hl, 0, 0..15, Highlight::Normal);
+        assert_hl(&hl, 1, 2..7, Highlight::Keyword);
+        assert_hl(&hl, 1, 7..9, Highlight::Normal);
+        assert_hl(&hl, 1, 9..16, Highlight::Number);
+        assert_hl(&hl, 2, 2..11, Highlight::Keyword);
+        assert_hl(&hl, 2, 13..18, Highlight::Number);
+        assert_hl(&hl, 2, 20..25, Highlight::Keyword);
+        assert_hl(&hl, 2, 27..30, Highlight::Number);
+        assert_hl(&hl, 4, 0..6, Highlight::Attribute);
+        assert_hl(&hl, 4, 6..17, Highlight::Normal);
+    }
+
+    #[test]
+    fn html_element() {
+        let hl = highlight(
+            Language::Html,
+            &[
+                "<!-- <p>hidden</p>",
+                "-->",
+                r#"<a href="x.html" disabled>Tom &amp; Jerry</a>"#,
+                "<p>don't</p>",
+            ],
+        );
+        assert_hl(&hl, 0, 0..18, Highlight::Comment);
+        assert_hl(&hl, 1, 0..3, Highlight::Comment);
+        assert_hl(&hl, 2, 0..2, Highlight::Keyword);
+        assert_hl(&hl, 2, 2..3, Highlight::Normal);
+        assert_hl(&hl, 2, 3..7, Highlight::Attribute);
+        assert_hl(&hl, 2, 7..8, Highlight::Normal);
+        assert_hl(&hl, 2, 8..16, Highlight::String);
+        assert_hl(&hl, 2, 17..25, Highlight::Attribute);
+        assert_hl(&hl, 2, 25..30, Highlight::Normal);
+        assert_hl(&hl, 2, 30..35, Highlight::Escape);
+        assert_hl(&hl, 2, 35..41, Highlight::Normal);
+        assert_hl(&hl, 2, 41..44, Highlight::Keyword);
+        assert_hl(&hl, 2, 44..45, Highlight::Normal);
+        assert_hl(&hl, 3, 0..2, Highlight::Keyword);
+        assert_hl(&hl, 3, 3..8, Highlight::Normal);
+    }
+
+    #[test]
+    fn shell_variables() {
+        let hl = highlight(
+            Language::Shell,
+            &[
+                r#"echo "$HOME""#,
+                "# comment",
+                r#"x=${FOO:-bar} $(ls $1) '$HOME\n'"#,
+                "if [ -f a ]; then",
+            ],
+        );
+        assert_hl(&hl, 0, 0..4, Highlight::Keyword);
+        assert_hl(&hl, 0, 5..6, Highlight::String);
+        assert_hl(&hl, 0, 6..11, Highlight::Variable);
+        assert_hl(&hl, 0, 11..12, Highlight::String);
+        assert_hl(&hl, 1, 0..9, Highlight::Comment);
+        assert_hl(&hl, 2, 2..13, Highlight::Variable);
+        assert_hl(&hl, 2, 14..22, Highlight::Variable);
+        assert_hl(&hl, 2, 23..32, Highlight::String);
+        assert_hl(&hl, 3, 0..2, Highlight::Statement);
+        assert_hl(&hl, 3, 13..17, Highlight::Statement);
+    }
+
+    #[test]
+    fn sql_case_insensitive_keywords() {
+        let hl = highlight(
+            Language::Sql,
+            &["select * FROM t WHERE selected = 'it''s\\n' -- c"],
+        );
+        assert_hl(&hl, 0, 0..6, Highlight::Keyword);
+        assert_hl(&hl, 0, 9..13, Highlight::Keyword);
+        assert_hl(&hl, 0, 16..21, Highlight::Keyword);
+        assert_hl(&hl, 0, 22..30, Highlight::Normal);
+        assert_hl(&hl, 0, 33..41, Highlight::String);
+        assert_hl(&hl, 0, 43..47, Highlight::Comment);
+    }
+
+    #[test]
+    fn json_object_key() {
+        let hl = highlight(
+            Language::Json,
+            &[r#"{"name": "kiro", "n" : 3, "a\"b": [true, null, "x"]}"#],
+        );
+        assert_hl(&hl, 0, 1..7, Highlight::Key);
+        assert_hl(&hl, 0, 7..9, Highlight::Normal);
+        assert_hl(&hl, 0, 9..15, Highlight::String);
+        assert_hl(&hl, 0, 17..20, Highlight::Key);
+        assert_hl(&hl, 0, 23..24, Highlight::Number);
+        assert_hl(&hl, 0, 26..32, Highlight::Key);
+        assert_hl(&hl, 0, 35..39, Highlight::Boolean);
+        assert_hl(&hl, 0, 41..45, Highlight::Keyword);
+        assert_hl(&hl, 0, 47..50, Highlight::String);
+    }
+
+    #[test]
+    fn yaml_mapping() {
+        let hl = highlight(
+            Language::Yaml,
+            &[
+                "key: value # c",
+                r#"- name: "a: b""#,
+                "  'quoted key': &a 1",
+                "ref: *a",
+                "t: !!str yes",
+                "# comment",
+                "url: http://example.com",
+            ],
+        );
+        assert_hl(&hl, 0, 0..3, Highlight::Key);
+        assert_hl(&hl, 0, 3..11, Highlight::Normal);
+        assert_hl(&hl, 0, 11..14, Highlight::Comment);
+        assert_hl(&hl, 1, 0..2, Highlight::Normal);
+        assert_hl(&hl, 1, 2..6, Highlight::Key);
+        assert_hl(&hl, 1, 8..14, Highlight::String);
+        assert_hl(&hl, 2, 2..14, Highlight::Key);
+        assert_hl(&hl, 2, 16..18, Highlight::Variable);
+        assert_hl(&hl, 2, 19..20, Highlight::Number);
+        assert_hl(&hl, 3, 5..7, Highlight::Variable);
+        assert_hl(&hl, 4, 3..8, Highlight::Type);
+        assert_hl(&hl, 4, 9..12, Highlight::Boolean);
+        assert_hl(&hl, 5, 0..9, Highlight::Comment);
+        assert_hl(&hl, 6, 0..3, Highlight::Key);
+        assert_hl(&hl, 6, 3..23, Highlight::Normal);
+    }
+
+    #[test]
+    fn markdown_document() {
+        let hl = highlight(
+            Language::Markdown,
+            &[
+                "# Title",
+                "Some `inline` and **bold** and *it* snake_case",
+                "* item",
+                "```rust",
+                "let x = 1; // *x*",
+                "```",
+                "#hashtag",
+            ],
+        );
+        assert_hl(&hl, 0, 0..7, Highlight::Heading);
+        assert_hl(&hl, 1, 0..5, Highlight::Normal);
+        assert_hl(&hl, 1, 5..13, Highlight::String);
+        assert_hl(&hl, 1, 13..18, Highlight::Normal);
+        assert_hl(&hl, 1, 18..26, Highlight::Emphasis);
+        assert_hl(&hl, 1, 26..31, Highlight::Normal);
+        assert_hl(&hl, 1, 31..35, Highlight::Emphasis);
+        assert_hl(&hl, 1, 35..46, Highlight::Normal);
+        assert_hl(&hl, 2, 0..6, Highlight::Normal);
+        assert_hl(&hl, 3, 0..7, Highlight::Comment);
+        assert_hl(&hl, 4, 0..17, Highlight::String);
+        assert_hl(&hl, 5, 0..3, Highlight::Comment);
+        assert_hl(&hl, 6, 0..8, Highlight::Normal);
+    }
+
+    #[test]
+    fn go_raw_string() {
+        let hl = highlight(Language::Go, &[r"s := `line1 \n", r"line2` + 1"]);
+        assert_hl(&hl, 0, 0..1, Highlight::Normal);
+        assert_hl(&hl, 0, 5..14, Highlight::String);
+        assert_hl(&hl, 1, 0..6, Highlight::String);
+        assert_hl(&hl, 1, 7..8, Highlight::Operator);
+        assert_hl(&hl, 1, 9..10, Highlight::Number);
+    }
+
+    #[test]
+    fn screaming_case_constant() {
+        let hl = highlight(Language::Rust, &["MAX_LEN + Max + X + PI + A1 + _"]);
+        assert_hl(&hl, 0, 0..7, Highlight::Constant);
+        assert_hl(&hl, 0, 10..13, Highlight::Normal);
+        assert_hl(&hl, 0, 16..17, Highlight::Normal);
+        assert_hl(&hl, 0, 20..22, Highlight::Constant);
+        assert_hl(&hl, 0, 25..27, Highlight::Constant);
+        assert_hl(&hl, 0, 30..31, Highlight::Normal);
+    }
+
+    #[test]
+    fn edit_restarts_highlighting_from_invalidated_line() {
+        let mut lines = vec!["let x = 1;"; 10_001];
+        lines[10_000] = "/* comment";
+        let mut rows: Vec<_> = lines.iter().map(|l| Row::new(*l).unwrap()).collect();
+        let mut hl = Highlighting::new(Language::Rust, &rows);
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 10_000, 0..10, Highlight::Comment);
+
+        // Lines before the edited line are not highlighted again
+        set_highlight(&mut hl, 0, 0, Highlight::Match);
+        rows[10_000] = Row::new("let y = 2;").unwrap();
+        hl.invalidate_line(10_000);
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 0, 0..1, Highlight::Match);
+        assert_hl(&hl, 10_000, 0..3, Highlight::Keyword);
+        assert_hl(&hl, 10_000, 8..9, Highlight::Number);
+
+        // Highlighting restarts with the state at end of previous line
+        rows[9_999] = Row::new("/* open").unwrap();
+        hl.invalidate_line(9_999);
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 10_000, 0..10, Highlight::Comment);
+        assert_hl(&hl, 0, 0..1, Highlight::Match);
+    }
+
+    #[test]
+    fn edit_cascades_only_while_line_state_changes() {
+        let mut rows: Vec<_> = ["let a = 1;", "let b = 2;", "let c = 3;"]
+            .iter()
+            .map(|l| Row::new(*l).unwrap())
+            .collect();
+        let mut hl = Highlighting::new(Language::Rust, &rows);
+        hl.update(&rows, rows.len());
+
+        // Editing self-contained line does not highlight following lines again
+        set_highlight(&mut hl, 2, 0, Highlight::Match);
+        rows[1] = Row::new("let b = 'x';").unwrap();
+        hl.invalidate_line(1);
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 1, 8..11, Highlight::Char);
+        assert_hl(&hl, 2, 0..1, Highlight::Match);
+
+        // Unterminated block comment cascades to following lines
+        rows[0] = Row::new("/* a").unwrap();
+        hl.invalidate_line(0);
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 1, 0..12, Highlight::Comment);
+        assert_hl(&hl, 2, 0..10, Highlight::Comment);
+
+        // Closing the comment cascades again
+        rows[0] = Row::new("/* a */").unwrap();
+        hl.invalidate_line(0);
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 1, 0..3, Highlight::Keyword);
+        assert_hl(&hl, 2, 0..3, Highlight::Keyword);
+    }
+
+    #[test]
+    fn color_scheme_overrides_color() {
+        let mut hl = highlight(Language::Rust, &["fn f() {}"]);
+        assert_eq!(
+            hl.color_scheme().color(hl.highlight_at(0, 0).unwrap()),
+            Color::Blue
+        );
+
+        let mut scheme = ColorScheme::default();
+        scheme.set(Highlight::Keyword, Color::Red);
+        hl.set_color_scheme(scheme);
+        assert_eq!(
+            hl.color_scheme().color(hl.highlight_at(0, 0).unwrap()),
+            Color::Red
+        );
+        assert_eq!(
+            hl.color_scheme().color(hl.highlight_at(0, 3).unwrap()),
+            Color::Yellow
+        );
+    }
+
+    #[test]
+    fn syntax_loaded_from_definition() {
+        let lang = syntax_file::load_syntax(
+            r##"
+name = "toy"
+file_extensions = ["toy"]
+line_comment = "#"
+keywords = ["let"]
+builtin_types = ["int"]
+string_quotes = ["'"]
+number = true
+"##,
+        )
+        .unwrap();
+        assert_eq!(lang, Language::Custom("toy"));
+
+        let hl = highlight(lang, &["let x: int = 'a' + 1 # done"]);
+        assert_hl(&hl, 0, 0..3, Highlight::Keyword);
+        assert_hl(&hl, 0, 3..7, Highlight::Normal);
+        assert_hl(&hl, 0, 7..10, Highlight::Type);
+        assert_hl(&hl, 0, 13..16, Highlight::String);
+        assert_hl(&hl, 0, 19..20, Highlight::Number);
+        assert_hl(&hl, 0, 21..27, Highlight::Comment);
+    }
+
+    #[test]
+    fn highlight_at_position() {
+        let hl = highlight(Language::Rust, &["fn f() {}"]);
+        assert_eq!(hl.highlight_at(0, 0), Some(Highlight::Keyword));
+        assert_eq!(hl.highlight_at(0, 1), Some(Highlight::Keyword));
+        assert_eq!(hl.highlight_at(0, 2), Some(Highlight::Normal));
+        assert_eq!(hl.highlight_at(0, 9), None);
+        assert_eq!(hl.highlight_at(1, 0), None);
+    }
+
+    #[test]
+    fn highlight_spans() {
+        let hl = highlight(Language::Rust, &["let x", ""]);
+        let spans: Vec<_> = hl.spans(0).collect();
+        assert_eq!(
+            spans,
+            vec![
+                (0, 3, Highlight::Keyword),
+                (3, 4, Highlight::Normal),
+                (4, 5, Highlight::Definition),
+            ],
+        );
+        assert_eq!(hl.spans(1).count(), 0);
+        assert_eq!(hl.spans(2).count(), 0);
+    }
+
+    #[test]
+    fn run_length_encoding_of_large_file() {
+        let lines: Vec<_> = include_str!("highlight.rs")
+            .lines()
+            .cycle()
+            .take(100_000)
+            .collect();
+        let hl = highlight(Language::Rust, &lines);
+
+        // Decoded highlights are identical to highlights of each character
+        let mut highlighter = Highlighter::new(&hl.syntax, &hl.keywords, LineState::default());
+        let mut bytes_per_char = 0;
+        for (y, line) in lines.iter().enumerate() {
+            let row = Row::new(*line).unwrap();
+            let row = row.render_text();
+            let mut expected = vec![Highlight::Normal; row.chars().count()];
+            highlighter.highlight_line(&mut expected, row);
+            highlight_todo_markers(&mut expected, row);
+            highlight_links(&mut expected, row);
+            assert!(hl.line(y).eq(expected.iter().copied()), "line {}", y);
+            bytes_per_char += expected.len() * std::mem::size_of::<Highlight>();
+        }
+
+        let bytes_runs: usize = hl
+            .lines
+            .iter()
+            .map(|l| l.len() * std::mem::size_of::<HighlightRun>())
+            .sum();
+        assert!(
+            bytes_runs < bytes_per_char,
+            "{} vs {}",
+            bytes_runs,
+            bytes_per_char
+        );
+    }
+
+    #[test]
+    fn split_long_run() {
+        let line = "a".repeat(u16::MAX as usize + 10);
+        let hl = highlight(Language::Plain, &[&line]);
+        assert_eq!(
+            hl.lines[0],
+            vec![(u16::MAX, Highlight::Normal), (10, Highlight::Normal)]
+        );
+        assert_eq!(
+            hl.highlight_at(0, u16::MAX as usize + 9),
+            Some(Highlight::Normal)
+        );
+        assert_eq!(hl.highlight_at(0, u16::MAX as usize + 10), None);
+    }
+
+    #[test]
+    fn highlight_all_matches() {
+        let rows: Vec<_> = ["let a = 1;", "let b = a;", "a"]
+            .iter()
+            .map(|l| Row::new(*l).unwrap())
+            .collect();
+        let mut hl = Highlighting::new(Language::Rust, &rows);
+        hl.update(&rows, rows.len());
+
+        let region = |hl, (sx, sy), (ex, ey)| RegionHighlight {
+            hl,
+            start: (sx, sy),
+            end: (ex, ey),
+        };
+        hl.set_matches(vec![
+            region(Highlight::Match, (4, 0), (5, 0)),
+            region(Highlight::Match, (8, 1), (9, 1)),
+            region(Highlight::Search, (0, 2), (1, 2)), // Current match
+        ]);
+        hl.needs_update = true;
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 0, 4..5, Highlight::Match);
+        assert_hl(&hl, 1, 8..9, Highlight::Match);
+        assert_hl(&hl, 2, 0..1, Highlight::Search);
+        assert_hl(&hl, 0, 0..3, Highlight::Keyword);
+
+        assert_eq!(hl.clear_previous_match(), Some(0));
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 0, 4..5, Highlight::Definition);
+        assert_hl(&hl, 1, 8..9, Highlight::Normal);
+        assert_hl(&hl, 2, 0..1, Highlight::Normal);
+        assert_eq!(hl.clear_previous_match(), None);
+    }
+
+    #[test]
+    fn matching_bracket() {
+        let rows: Vec<_> = ["fn f() { g(\"}\"); }", "{", "  [x]", "}"]
+            .iter()
+            .map(|l| Row::new(*l).unwrap())
+            .collect();
+        let mut hl = Highlighting::new(Language::Rust, &rows);
+        hl.update(&rows, rows.len());
+
+        assert_eq!(hl.highlight_matching_bracket(&rows, 7, 0), Some(0));
+        assert_hl(&hl, 0, 7..8, Highlight::Bracket);
+        assert_hl(&hl, 0, 11..14, Highlight::String);
+        assert_hl(&hl, 0, 17..18, Highlight::Bracket);
+
+        // Moving cursor restores highlights of previous brackets
+        assert_eq!(hl.highlight_matching_bracket(&rows, 0, 3), Some(0));
+        assert_hl(&hl, 0, 7..8, Highlight::Normal);
+        assert_hl(&hl, 0, 17..18, Highlight::Normal);
+        assert_hl(&hl, 1, 0..1, Highlight::Bracket);
+        assert_hl(&hl, 3, 0..1, Highlight::Bracket);
+
+        // Brackets are highlighted again after the lines are highlighted again
+        hl.invalidate_line(1);
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 1, 0..1, Highlight::Bracket);
+        assert_eq!(hl.highlight_matching_bracket(&rows, 4, 2), Some(1));
+        assert_hl(&hl, 1, 0..1, Highlight::Normal);
+        assert_hl(&hl, 2, 2..3, Highlight::Bracket);
+        assert_hl(&hl, 2, 4..5, Highlight::Bracket);
+
+        // Not a bracket
+        assert_eq!(hl.highlight_matching_bracket(&rows, 3, 2), Some(2));
+        assert!(hl.spans(2).all(|(_, _, h)| h != Highlight::Bracket));
+        // Bracket in string
+        assert_eq!(hl.highlight_matching_bracket(&rows, 12, 0), None);
+    }
+
+    #[test]
+    fn unbalanced_bracket() {
+        let rows = [Row::new("let v = (1, (2);").unwrap()];
+        let mut hl = Highlighting::new(Language::Rust, &rows);
+        hl.update(&rows, rows.len());
+        assert_eq!(hl.highlight_matching_bracket(&rows, 8, 0), None);
+        assert!(hl.spans(0).all(|(_, _, h)| h != Highlight::Bracket));
+        assert_eq!(hl.highlight_matching_bracket(&rows, 12, 0), Some(0));
+        assert_hl(&hl, 0, 12..13, Highlight::Bracket);
+        assert_hl(&hl, 0, 14..15, Highlight::Bracket);
+    }
+
+    #[test]
+    fn trailing_whitespace() {
+        let lines = ["let x = 1;   ", "  ", "// \t", "", "a"];
+        let mut hl = highlight(Language::Rust, &lines);
+        assert_hl(&hl, 0, 10..13, Highlight::Normal);
+
+        let rows: Vec<_> = lines.iter().map(|l| Row::new(*l).unwrap()).collect();
+        hl.set_trailing_whitespace(TrailingWhitespace::NonBlankLines);
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 0, 9..10, Highlight::Normal);
+        assert_hl(&hl, 0, 10..13, Highlight::TrailingWhitespace);
+        assert_hl(&hl, 1, 0..2, Highlight::Normal);
+        assert_hl(&hl, 2, 0..2, Highlight::Comment);
+        assert_hl(&hl, 2, 2..8, Highlight::TrailingWhitespace); // Tab is rendered as spaces
+        assert_hl(&hl, 4, 0..1, Highlight::Normal);
+
+        hl.set_trailing_whitespace(TrailingWhitespace::AllLines);
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 1, 0..2, Highlight::TrailingWhitespace);
+        assert_eq!(hl.spans(3).count(), 0);
+    }
+
+    #[test]
+    fn block_comment_with_same_start_and_end() {
+        const SYNTAX: SyntaxHighlight = SyntaxHighlight {
+            lang: Language::Python,
+            string_quotes: Cow::Borrowed(&[StringKind::new('\''), StringKind::new('"')]),
+            block_comment: Some((Cow::Borrowed("'''"), Cow::Borrowed("'''"))),
+            ..PLAIN_SYNTAX
+        };
+
+        let lines = ["x '''text''' y", "'''", "still 'comment'", "''' z", "''''"];
+        let keywords = KeywordTable::new(&SYNTAX);
+        let mut highlighter = Highlighter::new(&SYNTAX, &keywords, LineState::default());
+        let hls: Vec<_> = lines
+            .iter()
+            .map(|line| {
+                let mut out = vec![Highlight::Normal; line.len()];
+                highlighter.highlight_line(&mut out, line);
+                out
+            })
+            .collect();
+
+        let expected: &[&[(Range<usize>, Highlight)]] = &[
+            &[
+                (0..2, Highlight::Normal),
+                (2..12, Highlight::Comment),
+                (12..14, Highlight::Normal),
+            ],
+            &[(0..3, Highlight::Comment)],
+            &[(0..15, Highlight::Comment)],
+            &[(0..3, Highlight::Comment), (3..5, Highlight::Normal)],
+            // Opened with ''' and followed by single quote in the comment
+            &[(0..4, Highlight::Comment)],
+        ];
+        for (y, spans) in expected.iter().enumerate() {
+            for (xs, hl) in spans.iter() {
+                for x in xs.clone() {
+                    assert_eq!(hls[y][x], *hl, "at x={} y={}: {:?}", x, y, hls[y]);
+                }
+            }
+        }
+        assert_eq!(highlighter.state.block_comment_depth, 1);
+    }
+
+    #[test]
+    fn block_comment_delimiters_do_not_overlap() {
+        let hl = highlight(Language::C, &["/*/ x */ y"]);
+        assert_hl(&hl, 0, 0..8, Highlight::Comment);
+        assert_hl(&hl, 0, 8..10, Highlight::Normal);
+
+        // Starting in comment, '*/*' is closing '*/' followed by '*'. '/' is not shared with the
+        // next '/*'. '/*/' is '/' followed by closing '*/'
+        let hl = highlight(
+            Language::C,
+            &["/*", "*/* x", "/*", "*//* x */ y", "/*", "/*/ z"],
+        );
+        assert_hl(&hl, 1, 0..2, Highlight::Comment);
+        assert_hl(&hl, 1, 2..3, Highlight::Operator);
+        assert_hl(&hl, 1, 4..5, Highlight::Normal);
+        assert_hl(&hl, 3, 0..9, Highlight::Comment);
+        assert_hl(&hl, 3, 9..11, Highlight::Normal);
+        assert_hl(&hl, 5, 0..3, Highlight::Comment);
+        assert_hl(&hl, 5, 3..5, Highlight::Normal);
+
+        // Starting out of comment, '*/*' is '*' followed by opening '/*'
+        let hl = highlight(Language::C, &["a */* x */ y"]);
+        assert_hl(&hl, 0, 2..3, Highlight::Operator);
+        assert_hl(&hl, 0, 3..10, Highlight::Comment);
+        assert_hl(&hl, 0, 10..12, Highlight::Normal);
+
+        // In nested comment, '*/*' closes only the inner comment. '/*/' opens one more level
+        let hl = highlight(
+            Language::Rust,
+            &["/* /*", "*/* x */ y", "/* a", "/*/ */ b */ c"],
+        );
+        assert_hl(&hl, 1, 0..8, Highlight::Comment);
+        assert_hl(&hl, 1, 8..10, Highlight::Normal);
+        assert_hl(&hl, 3, 0..11, Highlight::Comment);
+        assert_hl(&hl, 3, 11..13, Highlight::Normal);
+    }
+
+    #[test]
+    fn unicode_identifier() {
+        let hl = highlight(
+            Language::Rust,
+            &[
+                "let café = naïve(1);",
+                "let cafe\u{301} = 1;",
+                "if\u{3000}x、return;",
+                "let 変数 = true;",
+                "iffé",
+            ],
+        );
+        assert_hl(&hl, 0, 4..8, Highlight::Definition);
+        assert_hl(&hl, 0, 11..16, Highlight::Function);
+        assert_hl(&hl, 0, 17..18, Highlight::Number);
+        assert_hl(&hl, 1, 4..9, Highlight::Definition);
+        assert_hl(&hl, 1, 12..13, Highlight::Number);
+        assert_hl(&hl, 2, 0..2, Highlight::Statement);
+        assert_hl(&hl, 2, 2..5, Highlight::Normal);
+        assert_hl(&hl, 2, 5..11, Highlight::Statement);
+        assert_hl(&hl, 3, 4..6, Highlight::Definition);
+        assert_hl(&hl, 3, 9..13, Highlight::Boolean);
+        assert_hl(&hl, 4, 0..4, Highlight::Normal);
+    }
+
+    #[test]
+    fn match_out_of_range() {
+        let rows = [Row::new("abc").unwrap()];
+        let mut hl = Highlighting::new(Language::Plain, &rows);
+        hl.update(&rows, rows.len());
+
+        let region = |start, end| RegionHighlight {
+            hl: Highlight::Match,
+            start,
+            end,
         };
+        hl.set_matches(vec![
+            region((1, 0), (10, 0)), // End is past the line length
+            region((0, 0), (2, 1)),  // End line is out of range
+            region((0, 5), (3, 5)),  // Entire region is out of range
+        ]);
+        hl.needs_update = true;
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 0, 0..3, Highlight::Match);
+        assert_eq!(hl.highlight_at(5, 0), None);
+
+        assert_eq!(hl.clear_previous_match(), Some(0));
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 0, 0..3, Highlight::Normal);
+    }
+
+    #[test]
+    fn char_literal_with_escape() {
+        let hl = highlight(
+            Language::Rust,
+            &[r"'\u{1F600}' '\x41' '\n' 'a' '\'' b'\\'", r"'\u{41'"],
+        );
+        assert_hl(&hl, 0, 0..11, Highlight::Char);
+        assert_hl(&hl, 0, 11..12, Highlight::Normal);
+        assert_hl(&hl, 0, 12..18, Highlight::Char);
+        assert_hl(&hl, 0, 18..19, Highlight::Normal);
+        assert_hl(&hl, 0, 19..23, Highlight::Char);
+        assert_hl(&hl, 0, 24..27, Highlight::Char);
+        assert_hl(&hl, 0, 28..32, Highlight::Char);
+        assert_hl(&hl, 0, 33..38, Highlight::Char);
+        // Unclosed escape sequence
+        assert_hl(&hl, 1, 0..1, Highlight::Normal);
+
+        let hl = highlight(Language::C, &[r"c = '\0'; w = L'\x41';"]);
+        assert_hl(&hl, 0, 4..8, Highlight::Char);
+        assert_hl(&hl, 0, 15..21, Highlight::Char);
+    }
+
+    #[test]
+    fn operators() {
+        let hl = highlight(
+            Language::Rust,
+            &[
+                "x::y(a) => b == c;",
+                r#"let s = "a=b"; // x == y"#,
+                "v -= 1e-5 + -2;",
+                "println!(\"{}\", a != b);",
+            ],
+        );
+        assert_hl(&hl, 0, 1..3, Highlight::Operator);
+        assert_hl(&hl, 0, 3..4, Highlight::Function);
+        assert_hl(&hl, 0, 7..8, Highlight::Normal);
+        assert_hl(&hl, 0, 8..10, Highlight::Operator);
+        assert_hl(&hl, 0, 10..13, Highlight::Normal);
+        assert_hl(&hl, 0, 13..15, Highlight::Operator);
+        assert_hl(&hl, 1, 6..7, Highlight::Operator);
+        assert_hl(&hl, 1, 8..13, Highlight::String);
+        assert_hl(&hl, 1, 15..24, Highlight::Comment);
+        assert_hl(&hl, 2, 2..4, Highlight::Operator);
+        assert_hl(&hl, 2, 5..9, Highlight::Number);
+        assert_hl(&hl, 2, 10..11, Highlight::Operator);
+        assert_hl(&hl, 2, 12..13, Highlight::Operator);
+        assert_hl(&hl, 2, 13..14, Highlight::Number);
+        assert_hl(&hl, 3, 0..8, Highlight::Macro);
+        assert_hl(&hl, 3, 17..19, Highlight::Operator);
+
+        // Syntax without operators
+        let hl = highlight(Language::Python, &["a == b"]);
+        assert_hl(&hl, 0, 0..6, Highlight::Normal);
+    }
+
+    #[test]
+    fn string_line_continuation() {
+        let src = &[r#"s = "foo\"#, r#"bar"; x"#, r#""baz"#, "y", r#""a\\"#, "z"];
+        let hl = highlight(Language::C, src);
+        assert_hl(&hl, 0, 4..8, Highlight::String);
+        assert_hl(&hl, 0, 8..9, Highlight::Escape);
+        assert_hl(&hl, 1, 0..4, Highlight::String);
+        assert_hl(&hl, 1, 6..7, Highlight::Normal);
+        // Not continued
+        assert_hl(&hl, 2, 0..4, Highlight::String);
+        assert_hl(&hl, 3, 0..1, Highlight::Normal);
+        assert_hl(&hl, 4, 0..2, Highlight::String);
+        assert_hl(&hl, 4, 2..4, Highlight::Escape);
+        assert_hl(&hl, 5, 0..1, Highlight::Normal);
+
+        let hl = highlight(Language::TypeScript, &["s = `foo", "bar`; 'a", "x"]);
+        assert_hl(&hl, 1, 0..4, Highlight::String);
+        assert_hl(&hl, 1, 6..8, Highlight::String);
+        assert_hl(&hl, 2, 0..1, Highlight::Normal);
+    }
+
+    #[test]
+    fn unterminated_string_ends_at_line_end() {
+        let src = &[r#"s = "abc"#, "x = 1;"];
+        let hl = highlight(Language::C, src);
+        assert_hl(&hl, 0, 4..8, Highlight::String);
+        assert_hl(&hl, 1, 0..1, Highlight::Normal);
+        assert_hl(&hl, 1, 4..5, Highlight::Number);
+
+        let hl = highlight(Language::Go, src);
+        assert_hl(&hl, 1, 0..1, Highlight::Normal);
+
+        // String literal in Rust can contain newlines
+        let hl = highlight(Language::Rust, src);
+        assert_hl(&hl, 1, 0..6, Highlight::String);
+
+        let hl = highlight(Language::Python, &[r"s = 'abc\", "def'", "'x", "y"]);
+        assert_hl(&hl, 1, 0..4, Highlight::String);
+        assert_hl(&hl, 3, 0..1, Highlight::Normal);
+    }
+
+    #[test]
+    fn php_variables_and_comments() {
+        let hl = highlight(
+            Language::Php,
+            &[
+                "<?php",
+                "echo $name; // comment",
+                "# comment",
+                r#"$s = "hi $name" . 'no $var';"#,
+                "function greet() {}",
+            ],
+        );
+        assert_hl(&hl, 0, 0..5, Highlight::Operator);
+        assert_hl(&hl, 1, 0..4, Highlight::Keyword);
+        assert_hl(&hl, 1, 5..10, Highlight::Variable);
+        assert_hl(&hl, 1, 12..22, Highlight::Comment);
+        assert_hl(&hl, 2, 0..9, Highlight::Comment);
+        assert_hl(&hl, 3, 0..2, Highlight::Variable);
+        assert_hl(&hl, 3, 5..9, Highlight::String);
+        assert_hl(&hl, 3, 9..14, Highlight::Variable);
+        assert_hl(&hl, 3, 14..15, Highlight::String);
+        assert_hl(&hl, 3, 18..27, Highlight::String);
+        assert_hl(&hl, 4, 0..8, Highlight::Keyword);
+        assert_hl(&hl, 4, 9..14, Highlight::Definition);
+    }
+
+    #[test]
+    fn lua_long_brackets() {
+        let hl = highlight(
+            Language::Lua,
+            &[
+                "--[[ block",
+                "comment ]] local s = [[multi",
+                "line]] .. [==[a]]b]==] -- line",
+                "if x ~= nil then end",
+            ],
+        );
+        assert_hl(&hl, 0, 0..10, Highlight::Comment);
+        assert_hl(&hl, 1, 0..10, Highlight::Comment);
+        assert_hl(&hl, 1, 11..16, Highlight::Keyword);
+        assert_hl(&hl, 1, 21..28, Highlight::String);
+        assert_hl(&hl, 2, 0..6, Highlight::String);
+        assert_hl(&hl, 2, 7..9, Highlight::Operator);
+        assert_hl(&hl, 2, 10..22, Highlight::String);
+        assert_hl(&hl, 2, 23..30, Highlight::Comment);
+        assert_hl(&hl, 3, 0..2, Highlight::Statement);
+        assert_hl(&hl, 3, 5..7, Highlight::Operator);
+        assert_hl(&hl, 3, 8..11, Highlight::Boolean);
+        assert_hl(&hl, 3, 12..16, Highlight::Statement);
+    }
+
+    #[test]
+    fn haskell_comments_and_types() {
+        let hl = highlight(
+            Language::Haskell,
+            &[
+                "{- {- nested -} -} x",
+                "data Shape = Circle Float | Rect -- comment",
+                "f' = foldl' (+) 'a'",
+            ],
+        );
+        assert_hl(&hl, 0, 0..18, Highlight::Comment);
+        assert_hl(&hl, 0, 18..20, Highlight::Normal);
+        assert_hl(&hl, 1, 0..4, Highlight::Keyword);
+        assert_hl(&hl, 1, 5..10, Highlight::Definition);
+        assert_hl(&hl, 1, 13..19, Highlight::Type);
+        assert_hl(&hl, 1, 20..25, Highlight::Type);
+        assert_hl(&hl, 1, 28..32, Highlight::Type);
+        assert_hl(&hl, 1, 33..43, Highlight::Comment);
+        assert_hl(&hl, 2, 0..2, Highlight::Normal);
+        assert_hl(&hl, 2, 5..11, Highlight::Normal);
+        assert_hl(&hl, 2, 16..19, Highlight::Char);
+    }
+
+    #[test]
+    fn kotlin_string_templates() {
+        let hl = highlight(
+            Language::Kotlin,
+            &[
+                r#"val x = "hi $name, ${a + b}" /* comment */"#,
+                r#"val s = """$x"#,
+                r#"""" @Test fun f() {}"#,
+            ],
+        );
+        assert_hl(&hl, 0, 0..3, Highlight::Keyword);
+        assert_hl(&hl, 0, 4..5, Highlight::Definition);
+        assert_hl(&hl, 0, 8..12, Highlight::String);
+        assert_hl(&hl, 0, 12..17, Highlight::Variable);
+        assert_hl(&hl, 0, 17..19, Highlight::String);
+        assert_hl(&hl, 0, 19..27, Highlight::Variable);
+        assert_hl(&hl, 0, 27..28, Highlight::String);
+        assert_hl(&hl, 0, 29..42, Highlight::Comment);
+        assert_hl(&hl, 1, 8..11, Highlight::String);
+        assert_hl(&hl, 1, 11..13, Highlight::Variable);
+        assert_hl(&hl, 2, 0..3, Highlight::String);
+        assert_hl(&hl, 2, 4..9, Highlight::Annotation);
+        assert_hl(&hl, 2, 10..13, Highlight::Keyword);
+    }
+
+    #[test]
+    fn swift_interpolation_and_directives() {
+        let hl = highlight(
+            Language::Swift,
+            &[
+                r#"let s = "x = \(f(x)) \n""#,
+                "#if DEBUG",
+                "if #available(iOS 15, *) {}",
+                "#endif",
+            ],
+        );
+        assert_hl(&hl, 0, 0..3, Highlight::Keyword);
+        assert_hl(&hl, 0, 8..13, Highlight::String);
+        assert_hl(&hl, 0, 13..20, Highlight::Variable);
+        assert_hl(&hl, 0, 20..21, Highlight::String);
+        assert_hl(&hl, 0, 21..23, Highlight::Escape);
+        assert_hl(&hl, 0, 23..24, Highlight::String);
+        assert_hl(&hl, 1, 0..3, Highlight::Preprocessor);
+        assert_hl(&hl, 2, 0..2, Highlight::Statement);
+        assert_hl(&hl, 2, 3..13, Highlight::Preprocessor);
+        assert_hl(&hl, 3, 0..6, Highlight::Preprocessor);
+    }
+
+    #[test]
+    fn cpp_namespace_and_raw_string() {
+        let hl = highlight(
+            Language::Cpp,
+            &[
+                "namespace foo {",
+                r#"auto s = R"(raw \n)"; std::string t = u8R"x(a)"b"#,
+                r#"c)x";"#,
+            ],
+        );
+        assert_hl(&hl, 0, 0..9, Highlight::Keyword);
+        assert_hl(&hl, 0, 10..13, Highlight::Definition);
+        assert_hl(&hl, 1, 9..20, Highlight::String);
+        assert_hl(&hl, 1, 20..21, Highlight::Normal);
+        assert_hl(&hl, 1, 25..27, Highlight::Operator);
+        assert_hl(&hl, 1, 38..48, Highlight::String);
+        assert_hl(&hl, 2, 0..4, Highlight::String);
+        assert_hl(&hl, 2, 4..5, Highlight::Normal);
+    }
 
-        // Consume whole '/*' here. Otherwise such as '/*/' is wrongly accepted
-        Some(self.eat_n(out, input, Highlight::Comment, comment_delim.len()))
+    #[test]
+    fn java_annotation_and_class() {
+        let hl = highlight(
+            Language::Java,
+            &[
+                "/** Javadoc */",
+                "public class Foo extends Bar {",
+                "    @Override public String toString() { return \"a\" + 'b'; }",
+            ],
+        );
+        assert_hl(&hl, 0, 0..14, Highlight::DocComment);
+        assert_hl(&hl, 1, 0..6, Highlight::Keyword);
+        assert_hl(&hl, 1, 7..12, Highlight::Keyword);
+        assert_hl(&hl, 1, 13..16, Highlight::Definition);
+        assert_hl(&hl, 1, 17..24, Highlight::Keyword);
+        assert_hl(&hl, 2, 4..13, Highlight::Annotation);
+        assert_hl(&hl, 2, 21..27, Highlight::Type);
+        assert_hl(&hl, 2, 28..36, Highlight::Function);
+        assert_hl(&hl, 2, 41..47, Highlight::Statement);
+        assert_hl(&hl, 2, 48..51, Highlight::String);
+        assert_hl(&hl, 2, 54..57, Highlight::Char);
     }
 
-    fn highlight_line_comment(
-        &mut self,
-        leader: &str,
-        out: &mut [Highlight],
-        input: &str,
-    ) -> Option<ParseStep> {
-        if self.prev_quote.is_none() && input.starts_with(leader) {
-            // Highlight as comment until end of line
-            for hl in out.iter_mut() {
-                *hl = Highlight::Comment;
-            }
-            Some(ParseStep::Break)
-        } else {
-            None
-        }
+    #[test]
+    fn toml_tables_and_keys() {
+        let hl = highlight(
+            Language::Toml,
+            &[
+                "[package]",
+                r#"name = "kiro" # comment"#,
+                "[[bin]]",
+                r#""quoted key"= 'C:\path'"#,
+                "date = 1979-05-27T07:32:00Z",
+                "a.b=1_000",
+            ],
+        );
+        assert_hl(&hl, 0, 0..9, Highlight::Keyword);
+        assert_hl(&hl, 1, 0..4, Highlight::Key);
+        assert_hl(&hl, 1, 4..7, Highlight::Normal);
+        assert_hl(&hl, 1, 7..13, Highlight::String);
+        assert_hl(&hl, 1, 14..23, Highlight::Comment);
+        assert_hl(&hl, 2, 0..7, Highlight::Keyword);
+        assert_hl(&hl, 3, 0..12, Highlight::Key);
+        assert_hl(&hl, 3, 14..23, Highlight::String);
+        assert_hl(&hl, 4, 7..27, Highlight::Number);
+        assert_hl(&hl, 5, 0..3, Highlight::Key);
+        assert_hl(&hl, 5, 4..9, Highlight::Number);
     }
 
-    fn highlight_string(&mut self, c: char, out: &mut [Highlight]) -> Option<ParseStep> {
-        if let Some(q) = self.prev_quote {
-            // In string literal. XXX: "\\" is not highlighted correctly
-            if self.prev_char != '\\' && q == c {
-                self.prev_quote = None;
-            }
-            Some(self.eat_one(out, c, Highlight::String))
-        } else if self.syntax.string_quotes.contains(&c) {
-            self.prev_quote = Some(c);
-            Some(self.eat_one(out, c, Highlight::String))
-        } else {
-            None
-        }
+    #[test]
+    fn lang_change_clears_match() {
+        let rows: Vec<_> = ["let a = 1;", "let b = a;"]
+            .iter()
+            .map(|l| Row::new(*l).unwrap())
+            .collect();
+        let mut hl = Highlighting::new(Language::Rust, &rows);
+        hl.set_matches(vec![RegionHighlight {
+            hl: Highlight::Search,
+            start: (8, 1),
+            end: (9, 1),
+        }]);
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 1, 8..9, Highlight::Search);
+
+        let rows = vec![Row::new("a = 1").unwrap()];
+        hl.lang_changed(Language::Plain);
+        hl.update(&rows, rows.len());
+        assert_eq!(hl.lines.len(), 1);
+        assert_hl(&hl, 0, 0..5, Highlight::Normal);
+        assert_eq!(hl.clear_previous_match(), None);
     }
 
-    fn highlight_ident(&mut self, out: &mut [Highlight], input: &str) -> Option<ParseStep> {
-        fn lex_ident(mut input: &str) -> Option<&str> {
-            for (i, c) in input.char_indices() {
-                if is_sep(c) {
-                    input = &input[..i];
-                    break;
-                }
-            }
-            if input.is_empty() {
-                None
-            } else {
-                Some(input)
-            }
-        }
+    #[test]
+    fn disable_highlighting() {
+        let rows: Vec<_> = ["/* comment", "*/ fn f() {}"]
+            .iter()
+            .map(|l| Row::new(*l).unwrap())
+            .collect();
+        let mut hl = Highlighting::new(Language::Rust, &rows);
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 1, 3..5, Highlight::Keyword);
 
-        // let iter_words = |words: &'static [&'static str], hl| words.iter().zip(iter::repeat(hl));
-        fn iter_words<'a>(
-            words: &'a [&'a str],
-            hl: Highlight,
-        ) -> impl Iterator<Item = (&&'a str, Highlight)> {
-            words.iter().zip(iter::repeat(hl))
-        }
+        hl.set_enabled(false);
+        hl.set_matches(vec![RegionHighlight {
+            hl: Highlight::Match,
+            start: (6, 1),
+            end: (7, 1),
+        }]);
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 0, 0..10, Highlight::Normal);
+        assert_hl(&hl, 1, 0..6, Highlight::Normal);
+        assert_hl(&hl, 1, 6..7, Highlight::Match);
+        assert_hl(&hl, 1, 7..12, Highlight::Normal);
 
-        lex_ident(input).as_ref().and_then(|ident| {
-            use Highlight::*;
+        hl.set_enabled(true);
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 0, 0..10, Highlight::Comment);
+        assert_hl(&hl, 1, 3..5, Highlight::Keyword);
+        assert_hl(&hl, 1, 6..7, Highlight::Match);
+    }
 
-            let keyword = iter_words(self.syntax.keywords, Keyword)
-                .chain(iter_words(self.syntax.control_statements, Statement))
-                .chain(iter_words(self.syntax.builtin_types, Type))
-                .chain(iter_words(self.syntax.boolean_constants, Boolean))
-                .chain(iter_words(self.syntax.special_vars, SpecialVar))
-                .find(|(k, _)| *k == ident);
+    #[test]
+    fn tabs_are_expanded_in_render_text() {
+        let rows: Vec<_> = ["\tfn f() {", "\t\tlet x = 1;\t// c", "\t}"]
+            .iter()
+            .map(|l| Row::new(*l).unwrap())
+            .collect();
+        let mut hl = Highlighting::new(Language::Rust, &rows);
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 0, 0..8, Highlight::Normal);
+        assert_hl(&hl, 0, 8..10, Highlight::Keyword);
+        assert_hl(&hl, 0, 11..12, Highlight::Definition);
+        assert_hl(&hl, 1, 16..19, Highlight::Keyword);
+        assert_hl(&hl, 1, 24..25, Highlight::Number);
+        assert_hl(&hl, 1, 32..36, Highlight::Comment);
 
-            let definition = keyword.or_else(|| {
-                if self.after_def_keyword {
-                    Some((ident, Highlight::Definition))
-                } else {
-                    None
-                }
-            });
+        // Search match and bracket are given as positions in buffer
+        hl.set_matches(vec![RegionHighlight {
+            hl: Highlight::Match,
+            start: (6, 1),
+            end: (7, 1),
+        }]);
+        hl.needs_update = true;
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 1, 19..20, Highlight::Normal);
+        assert_hl(&hl, 1, 20..21, Highlight::Match);
+        assert_hl(&hl, 1, 21..22, Highlight::Normal);
 
-            if keyword.is_some() && self.syntax.definition_keywords.contains(&ident) {
-                self.after_def_keyword = true;
-            }
+        assert_eq!(hl.highlight_matching_bracket(&rows, 8, 0), Some(0));
+        assert_hl(&hl, 0, 15..16, Highlight::Bracket);
+        assert_hl(&hl, 2, 8..9, Highlight::Bracket);
+    }
 
-            let highlighted = keyword.or(definition);
-            highlighted.map(|(ident, hl)| self.eat_n(out, input, hl, ident.len()))
-        })
+    #[test]
+    fn javascript_regex_literal() {
+        let hl = highlight(
+            Language::JavaScript,
+            &[
+                "x = /ab+/g;",
+                "y = a / b / 2;",
+                r"if (/[/]\//.test(s)) return /a b/i",
+                "f() / 2 + /x",
+            ],
+        );
+        assert_hl(&hl, 0, 4..10, Highlight::Regex);
+        assert_hl(&hl, 0, 10..11, Highlight::Normal);
+        assert_hl(&hl, 1, 6..7, Highlight::Operator);
+        assert_hl(&hl, 1, 10..11, Highlight::Operator);
+        assert_hl(&hl, 1, 12..13, Highlight::Number);
+        assert_hl(&hl, 2, 4..11, Highlight::Regex);
+        assert_hl(&hl, 2, 11..12, Highlight::Normal);
+        assert_hl(&hl, 2, 21..27, Highlight::Statement);
+        assert_hl(&hl, 2, 28..34, Highlight::Regex);
+        assert_hl(&hl, 3, 4..5, Highlight::Operator);
+        // Not closed
+        assert_hl(&hl, 3, 10..11, Highlight::Operator);
+        assert_hl(&hl, 3, 11..12, Highlight::Normal);
     }
 
-    fn highlight_prefix_number(
-        &mut self,
-        num: NumLit,
-        is_bound: bool,
-        c: char,
-        out: &mut [Highlight],
-        input: &str,
-    ) -> Option<ParseStep> {
-        let prefix: &[_] = match num {
-            NumLit::Hex => b"0x",
-            NumLit::Bin => b"0b",
-            NumLit::Digit => unreachable!(),
-        };
+    #[test]
+    fn go_number_literals() {
+        let hl = highlight(
+            Language::Go,
+            &[
+                "0x1p-2 0x_1F 0x1.8p3i 1_000 3i 0o17 0b1 1.5e3i",
+                "const ( a = iota; b )",
+            ],
+        );
+        assert_hl(&hl, 0, 0..6, Highlight::Number);
+        assert_hl(&hl, 0, 13..21, Highlight::Number);
+        assert_hl(&hl, 0, 22..27, Highlight::Number);
+        assert_hl(&hl, 0, 28..30, Highlight::Number);
+        assert_hl(&hl, 0, 31..35, Highlight::Number);
+        assert_hl(&hl, 0, 36..39, Highlight::Number);
+        assert_hl(&hl, 0, 40..46, Highlight::Number);
+        assert_hl(&hl, 1, 12..16, Highlight::SpecialVar);
 
-        fn is_num_char(b: u8, num: NumLit, delim: Option<char>) -> bool {
-            match num {
-                NumLit::Hex if b.is_ascii_hexdigit() => true,
-                NumLit::Bin if b"01".contains(&b) => true,
-                _ => delim == Some(b as char),
-            }
-        }
+        // Not hexadecimal float
+        let hl = highlight(Language::Rust, &["0x1p2"]);
+        assert_hl(&hl, 0, 3..5, Highlight::Normal);
+    }
 
-        let bytes = input.as_bytes();
-        if is_bound {
-            if bytes.starts_with(prefix)
-                && bytes.len() > prefix.len()
-                && is_num_char(bytes[prefix.len()], num, self.syntax.number_delim)
-            {
-                self.num = num;
-                return Some(self.eat_n(out, input, Highlight::Number, prefix.len()));
-            }
-        } else if self.num == num
-            && self.prev_hl == Highlight::Number
-            && c.is_ascii()
-            && is_num_char(c as u8, num, self.syntax.number_delim)
-        {
-            return Some(self.eat_one(out, c, Highlight::Number));
-        }
+    #[test]
+    fn register_built_syntax() {
+        let syntax = SyntaxHighlightBuilder::new("toy-builder")
+            .line_comment("//")
+            .keyword("foo")
+            .string_quote('"')
+            .number(true)
+            .build();
+        let lang = Highlighting::register_syntax(syntax, &["toyb"]);
+        assert_eq!(lang, Language::Custom("toy-builder"));
+        assert_eq!(Language::detect("a.toyb"), lang);
 
-        None
+        let hl = highlight(lang, &["foo \"s\" 42 // foo", "food"]);
+        assert_hl(&hl, 0, 0..3, Highlight::Keyword);
+        assert_hl(&hl, 0, 4..7, Highlight::String);
+        assert_hl(&hl, 0, 8..10, Highlight::Number);
+        assert_hl(&hl, 0, 11..17, Highlight::Comment);
+        assert_hl(&hl, 1, 0..4, Highlight::Normal);
+
+        // Registering the language again replaces the syntax. The previous one is dropped when no
+        // buffer uses it
+        let old = Arc::downgrade(&hl.syntax);
+        let syntax = SyntaxHighlightBuilder::new("toy-builder")
+            .keyword("bar")
+            .build();
+        assert_eq!(Highlighting::register_syntax(syntax, &["toyb"]), lang);
+        assert!(old.upgrade().is_some());
+        drop(hl);
+        assert!(old.upgrade().is_none());
+
+        let hl = highlight(lang, &["foo bar"]);
+        assert_hl(&hl, 0, 0..3, Highlight::Normal);
+        assert_hl(&hl, 0, 4..7, Highlight::Keyword);
     }
 
-    fn highlight_digit_number(
-        &mut self,
-        is_bound: bool,
-        c: char,
-        out: &mut [Highlight],
-    ) -> Option<ParseStep> {
-        let prev_is_number = self.num == NumLit::Digit && self.prev_hl == Highlight::Number;
-        if is_bound {
-            if c.is_ascii_digit() || prev_is_number && c == '.' {
-                self.num = NumLit::Digit;
-                return Some(self.eat_one(out, c, Highlight::Number));
-            }
-        } else if prev_is_number && (self.syntax.number_delim == Some(c) || c.is_ascii_digit()) {
-            return Some(self.eat_one(out, c, Highlight::Number));
-        }
+    #[test]
+    fn leading_decimal_point() {
+        let hl = highlight(Language::C, &["x = .5 + 1. + .5e3f;", "a.b.5 f().5"]);
+        assert_hl(&hl, 0, 4..6, Highlight::Number);
+        assert_hl(&hl, 0, 9..11, Highlight::Number);
+        assert_hl(&hl, 0, 14..19, Highlight::Number);
+        assert_hl(&hl, 1, 3..4, Highlight::Normal);
+        assert_hl(&hl, 1, 9..10, Highlight::Normal);
 
-        None
+        let hl = highlight(Language::Rust, &["1..5 .5"]);
+        assert_hl(&hl, 0, 0..1, Highlight::Number);
+        assert_hl(&hl, 0, 1..3, Highlight::Operator);
+        assert_hl(&hl, 0, 3..4, Highlight::Number);
+        assert_hl(&hl, 0, 5..6, Highlight::Normal); // Rust does not allow .5
     }
 
-    fn highlight_char(&mut self, out: &mut [Highlight], input: &str) -> Option<ParseStep> {
-        if self.syntax.number_delim == Some('\'') && self.prev_hl == Highlight::Number {
-            return None; // Consider number literal delimiter in C++ (e.g. `123'456'789`)
-        }
+    #[test]
+    fn elixir_syntax() {
+        let hl = highlight(
+            Language::Elixir,
+            &[
+                "defmodule Foo.Bar do",
+                "  def run(x), do: {:ok, \"v#{x}\"} # done",
+                "  ~r/a+b/i |> ~s(str)",
+                "  @doc \"\"\"",
+                "  Doc #{1}",
+                "  \"\"\"",
+                "end",
+            ],
+        );
+        assert_hl(&hl, 0, 0..9, Highlight::Keyword);
+        assert_hl(&hl, 0, 10..13, Highlight::Type);
+        assert_hl(&hl, 0, 14..17, Highlight::Type);
+        assert_hl(&hl, 0, 18..20, Highlight::Keyword);
+        assert_hl(&hl, 1, 2..5, Highlight::Keyword);
+        assert_hl(&hl, 1, 6..9, Highlight::Definition);
+        assert_hl(&hl, 1, 19..22, Highlight::Symbol);
+        assert_hl(&hl, 1, 24..26, Highlight::String);
+        assert_hl(&hl, 1, 26..30, Highlight::Variable);
+        assert_hl(&hl, 1, 30..31, Highlight::String);
+        assert_hl(&hl, 1, 33..39, Highlight::Comment);
+        assert_hl(&hl, 2, 2..10, Highlight::Regex);
+        assert_hl(&hl, 2, 11..13, Highlight::Operator);
+        assert_hl(&hl, 2, 14..21, Highlight::String);
+        assert_hl(&hl, 3, 2..6, Highlight::Attribute);
+        assert_hl(&hl, 3, 7..10, Highlight::String);
+        assert_hl(&hl, 4, 2..6, Highlight::String);
+        assert_hl(&hl, 4, 6..10, Highlight::Variable);
+        assert_hl(&hl, 5, 2..5, Highlight::String);
+        assert_hl(&hl, 6, 0..3, Highlight::Keyword);
+    }
 
-        let mut i = input.chars();
-        let len = match (i.next(), i.next(), i.next(), i.next()) {
-            (Some('\''), Some('\\'), _, Some('\'')) => Some(4),
-            (Some('\''), _, Some('\''), _) => Some(3),
-            _ => None,
+    #[test]
+    fn ocaml_syntax() {
+        let hl = highlight(
+            Language::OCaml,
+            &[
+                "(* (* nested *) *) let x = 'a' in",
+                "(* outer (* inner *)",
+                "   still comment *) match y with Some _ -> \"s\"",
+                "let id (x : 'a) = x",
+            ],
+        );
+        assert_hl(&hl, 0, 0..18, Highlight::Comment);
+        assert_hl(&hl, 0, 19..22, Highlight::Keyword);
+        assert_hl(&hl, 0, 23..24, Highlight::Definition);
+        assert_hl(&hl, 0, 27..30, Highlight::Char);
+        assert_hl(&hl, 0, 31..33, Highlight::Keyword);
+        assert_hl(&hl, 1, 0..20, Highlight::Comment);
+        assert_hl(&hl, 2, 0..19, Highlight::Comment);
+        assert_hl(&hl, 2, 20..25, Highlight::Statement);
+        assert_hl(&hl, 2, 33..37, Highlight::Type);
+        assert_hl(&hl, 2, 43..46, Highlight::String);
+        assert_hl(&hl, 3, 12..14, Highlight::Normal); // Type variable is not a character
+    }
+
+    #[test]
+    fn force_refresh_highlights_all_lines() {
+        let rows: Vec<_> = ["fn a() {}", "let b = 1;"]
+            .iter()
+            .map(|l| Row::new(*l).unwrap())
+            .collect();
+        let mut hl = Highlighting::new(Language::Rust, &rows);
+        hl.update(&rows, rows.len());
+        set_highlight(&mut hl, 0, 0, Highlight::Normal);
+        set_highlight(&mut hl, 1, 0, Highlight::Normal);
+
+        // Screen did not grow. Nothing is highlighted again
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 0, 0..1, Highlight::Normal);
+
+        hl.force_refresh();
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 0, 0..2, Highlight::Keyword);
+        assert_hl(&hl, 1, 0..3, Highlight::Keyword);
+    }
+
+    #[test]
+    fn keyword_table_lookup() {
+        const SYNTAX: SyntaxHighlight = SyntaxHighlight {
+            keywords: Words::Static(&["if", "SELECT"]),
+            control_statements: Words::Static(&["if", "return"]),
+            boolean_constants: &["TRUE"],
+            ..PLAIN_SYNTAX
         };
+        let table = KeywordTable::new(&SYNTAX);
+        assert_eq!(table.get("if"), Some(Highlight::Keyword)); // The first list wins
+        assert_eq!(table.get("return"), Some(Highlight::Statement));
+        assert_eq!(table.get("select"), None);
+        assert_eq!(table.get("ret"), None);
 
-        len.map(|len| self.eat_n(out, input, Highlight::Char, len))
+        const SQL_LIKE: SyntaxHighlight = SyntaxHighlight {
+            case_insensitive_keywords: true,
+            ..SYNTAX
+        };
+        let table = KeywordTable::new(&SQL_LIKE);
+        assert_eq!(table.get("select"), Some(Highlight::Keyword));
+        assert_eq!(table.get("SeLeCt"), Some(Highlight::Keyword));
+        assert_eq!(table.get("true"), Some(Highlight::Boolean));
     }
 
-    fn highlight_one(&mut self, c: char, out: &mut [Highlight], input: &str) -> ParseStep {
-        if self.after_def_keyword && !c.is_ascii_whitespace() && is_sep(c) {
-            self.after_def_keyword = false;
-        }
+    #[test]
+    fn keyword_is_not_matched_as_prefix() {
+        let hl = highlight(
+            Language::Rust,
+            &[
+                "let structure = struct_name; structs; struct S;",
+                "fnord(); iffy",
+            ],
+        );
+        assert_hl(&hl, 0, 0..3, Highlight::Keyword);
+        assert_hl(&hl, 0, 4..13, Highlight::Definition);
+        assert_hl(&hl, 0, 16..27, Highlight::Normal);
+        assert_hl(&hl, 0, 29..36, Highlight::Normal);
+        assert_hl(&hl, 0, 38..44, Highlight::Keyword);
+        assert_hl(&hl, 1, 0..5, Highlight::Function);
+        assert_hl(&hl, 1, 9..13, Highlight::Normal);
 
-        macro_rules! try_highlight {
-            ($call:expr) => {
-                if let Some(step) = $call {
-                    return step;
-                }
-            };
-        }
+        let hl = highlight(Language::Sql, &["SELECT selection FROM fromage"]);
+        assert_hl(&hl, 0, 0..6, Highlight::Keyword);
+        assert_hl(&hl, 0, 7..16, Highlight::Normal);
+        assert_hl(&hl, 0, 17..21, Highlight::Keyword);
+        assert_hl(&hl, 0, 22..29, Highlight::Normal);
+    }
 
-        if let Some((comment_start, comment_end)) = self.syntax.block_comment {
-            try_highlight!(self.highlight_block_comment(comment_start, comment_end, c, out, input));
-        }
+    #[test]
+    fn nim_syntax() {
+        let hl = highlight(
+            Language::Nim,
+            &[
+                "#[ block #[ nested ]# ]# proc foo*(x: int): string =",
+                "  ## Doc comment",
+                "  result = r\"C:\\n\" & \"\"\"text\"\"\" # comment",
+                "  let n = 1'i8 + 0x1F",
+            ],
+        );
+        assert_hl(&hl, 0, 0..24, Highlight::Comment);
+        assert_hl(&hl, 0, 25..29, Highlight::Keyword);
+        assert_hl(&hl, 0, 30..33, Highlight::Definition);
+        assert_hl(&hl, 0, 38..41, Highlight::Type);
+        assert_hl(&hl, 0, 44..50, Highlight::Type);
+        assert_hl(&hl, 1, 2..16, Highlight::DocComment);
+        assert_hl(&hl, 2, 2..8, Highlight::SpecialVar);
+        assert_hl(&hl, 2, 11..18, Highlight::String);
+        assert_hl(&hl, 2, 21..31, Highlight::String);
+        assert_hl(&hl, 2, 32..41, Highlight::Comment);
+        assert_hl(&hl, 3, 2..5, Highlight::Keyword);
+        assert_hl(&hl, 3, 10..14, Highlight::Number);
+        assert_hl(&hl, 3, 17..21, Highlight::Number);
+    }
 
-        if let Some(comment_leader) = self.syntax.line_comment {
-            try_highlight!(self.highlight_line_comment(comment_leader, out, input));
-        }
+    #[test]
+    fn zig_syntax() {
+        let hl = highlight(
+            Language::Zig,
+            &[
+                "const std = @import(\"std\");",
+                "/// Doc",
+                "pub fn main() !void {",
+                "    const s =",
+                "        \\\\line \"one\\n",
+                "    ;",
+                "    var x: u8 = @intCast(0x1p3);",
+            ],
+        );
+        assert_hl(&hl, 0, 0..5, Highlight::Keyword);
+        assert_hl(&hl, 0, 6..9, Highlight::Definition);
+        assert_hl(&hl, 0, 12..19, Highlight::Builtin);
+        assert_hl(&hl, 0, 20..25, Highlight::String);
+        assert_hl(&hl, 1, 0..7, Highlight::DocComment);
+        assert_hl(&hl, 2, 0..3, Highlight::Keyword);
+        assert_hl(&hl, 2, 4..6, Highlight::Keyword);
+        assert_hl(&hl, 2, 7..11, Highlight::Definition);
+        assert_hl(&hl, 2, 15..19, Highlight::Type);
+        assert_hl(&hl, 4, 0..8, Highlight::Normal);
+        assert_hl(&hl, 4, 8..21, Highlight::String);
+        assert_hl(&hl, 5, 4..5, Highlight::Normal);
+        assert_hl(&hl, 6, 11..13, Highlight::Type);
+        assert_hl(&hl, 6, 16..24, Highlight::Builtin);
+        assert_hl(&hl, 6, 25..30, Highlight::Number);
+    }
 
-        if self.syntax.character {
-            try_highlight!(self.highlight_char(out, input));
-        }
+    #[test]
+    fn max_highlight_columns() {
+        let line = "var a=1;".repeat(12_500); // 100k characters of minified code
+        let rows = vec![
+            Row::new(line.as_str()).unwrap(),
+            Row::new("var b;").unwrap(),
+        ];
+        let mut hl = Highlighting::new(Language::JavaScript, &rows);
+        hl.set_max_highlight_columns(Some(1000));
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 0, 0..3, Highlight::Keyword);
+        assert_hl(&hl, 0, 992..995, Highlight::Keyword);
+        assert_hl(&hl, 0, 998..999, Highlight::Number);
+        // Characters after the limit are one Normal run (split by the maximum length of a run)
+        let rest: Vec<_> = hl.spans(0).filter(|(_, end, _)| *end > 999).collect();
+        assert_eq!(rest[0].0, 999);
+        assert!(
+            rest.iter().all(|(_, _, h)| *h == Highlight::Normal),
+            "{:?}",
+            rest
+        );
+        assert_hl(&hl, 1, 0..3, Highlight::Keyword);
 
-        if !self.syntax.string_quotes.is_empty() {
-            try_highlight!(self.highlight_string(c, out));
-        }
+        hl.set_max_highlight_columns(None);
+        hl.update(&rows, rows.len());
+        assert_hl(&hl, 0, 99_992..99_995, Highlight::Keyword);
+    }
 
-        let is_bound = is_sep(self.prev_char) ^ is_sep(c);
+    #[test]
+    fn scala_syntax() {
+        let hl = highlight(
+            Language::Scala,
+            &[
+                "val x = s\"hi $name, ${a + 1}\" /* block",
+                "  /* nested */ still */ def f(n: Int) = n match {",
+                "  case _ => \"\"\"raw $v\"\"\"",
+            ],
+        );
+        assert_hl(&hl, 0, 0..3, Highlight::Keyword);
+        assert_hl(&hl, 0, 4..5, Highlight::Definition);
+        assert_hl(&hl, 0, 8..13, Highlight::String);
+        assert_hl(&hl, 0, 13..18, Highlight::Variable);
+        assert_hl(&hl, 0, 18..20, Highlight::String);
+        assert_hl(&hl, 0, 20..28, Highlight::Variable);
+        assert_hl(&hl, 0, 28..29, Highlight::String);
+        assert_hl(&hl, 0, 30..38, Highlight::Comment);
+        assert_hl(&hl, 1, 0..23, Highlight::Comment);
+        assert_hl(&hl, 1, 24..27, Highlight::Keyword);
+        assert_hl(&hl, 1, 28..29, Highlight::Definition);
+        assert_hl(&hl, 1, 33..36, Highlight::Type);
+        assert_hl(&hl, 1, 42..47, Highlight::Statement);
+        assert_hl(&hl, 2, 2..6, Highlight::Statement);
+        assert_hl(&hl, 2, 12..19, Highlight::String);
+        assert_hl(&hl, 2, 19..21, Highlight::Variable);
+        assert_hl(&hl, 2, 21..24, Highlight::String);
+    }
 
-        // Highlight identifiers
-        if is_bound {
-            try_highlight!(self.highlight_ident(out, input));
-        }
+    #[test]
+    fn makefile_syntax() {
+        let hl = highlight(
+            Language::Makefile,
+            &[
+                "CC := gcc # compiler",
+                ".PHONY: all",
+                "all: deps $(OBJS)",
+                "\t$(CC) -o $@ $< ${LDFLAGS}",
+                "\tif [ -f \"$^\" ]; then echo $$HOME; fi",
+                "ifeq ($(OS),Linux)",
+            ],
+        );
+        assert_hl(&hl, 0, 0..10, Highlight::Normal);
+        assert_hl(&hl, 0, 10..20, Highlight::Comment);
+        assert_hl(&hl, 1, 0..6, Highlight::Definition);
+        assert_hl(&hl, 1, 6..11, Highlight::Normal);
+        assert_hl(&hl, 2, 0..3, Highlight::Definition);
+        assert_hl(&hl, 2, 3..10, Highlight::Normal);
+        assert_hl(&hl, 2, 10..17, Highlight::Variable);
+        // Tab is rendered as 8 spaces
+        assert_hl(&hl, 3, 8..13, Highlight::Variable);
+        assert_hl(&hl, 3, 17..19, Highlight::Variable);
+        assert_hl(&hl, 3, 20..22, Highlight::Variable);
+        assert_hl(&hl, 3, 23..33, Highlight::Variable);
+        assert_hl(&hl, 4, 8..10, Highlight::Statement);
+        assert_hl(&hl, 4, 16..17, Highlight::String);
+        assert_hl(&hl, 4, 34..36, Highlight::Variable);
+        assert_hl(&hl, 5, 0..4, Highlight::Keyword);
+        assert_hl(&hl, 5, 6..11, Highlight::Variable);
+    }
 
-        if self.syntax.hex_number {
-            try_highlight!(self.highlight_prefix_number(NumLit::Hex, is_bound, c, out, input));
-        }
+    #[test]
+    fn python_builtin_functions() {
+        let hl = highlight(
+            Language::Python,
+            &["print(len(x))", "printer(x)", "def len(self):"],
+        );
+        assert_hl(&hl, 0, 0..5, Highlight::Builtin);
+        assert_hl(&hl, 0, 6..9, Highlight::Builtin);
+        assert_hl(&hl, 0, 10..11, Highlight::Normal);
+        assert_hl(&hl, 1, 0..7, Highlight::Function);
+        assert_hl(&hl, 2, 0..3, Highlight::Keyword);
+        assert_hl(&hl, 2, 4..7, Highlight::Definition);
+        assert_hl(&hl, 2, 8..12, Highlight::SpecialVar);
+    }
 
-        if self.syntax.bin_number {
-            try_highlight!(self.highlight_prefix_number(NumLit::Bin, is_bound, c, out, input));
-        }
+    #[test]
+    fn comment_and_string_nesting() {
+        let hl = highlight(
+            Language::Rust,
+            &[
+                r#"let s = "// not a comment";"#,
+                r#"// "not a string"#,
+                r#"/* "not a string */ x"#,
+                r#"let s = "/* not a comment"; x"#,
+                r#"/* "multiline"#,
+                r#"string */ "s""#,
+            ],
+        );
+        assert_hl(&hl, 0, 8..26, Highlight::String);
+        assert_hl(&hl, 0, 26..27, Highlight::Normal);
+        assert_hl(&hl, 1, 0..16, Highlight::Comment);
+        assert_hl(&hl, 2, 0..19, Highlight::Comment);
+        assert_hl(&hl, 2, 19..21, Highlight::Normal);
+        assert_hl(&hl, 3, 8..26, Highlight::String);
+        assert_hl(&hl, 3, 26..29, Highlight::Normal);
+        assert_hl(&hl, 4, 0..13, Highlight::Comment);
+        assert_hl(&hl, 5, 0..9, Highlight::Comment);
+        assert_hl(&hl, 5, 9..10, Highlight::Normal);
+        assert_hl(&hl, 5, 10..13, Highlight::String);
+    }
 
-        if self.syntax.number {
-            try_highlight!(self.highlight_digit_number(is_bound, c, out));
-        }
+    #[test]
+    fn perl_syntax() {
+        let hl = highlight(
+            Language::Perl,
+            &[
+                "my $x = \"hi\"; # comment",
+                "my @list = qw(a (b) c); my %h = %$ref;",
+                "print $a % $b, q{x}, $y{qq};",
+                "sub hello {",
+                "=pod",
+                "my $doc = 1;",
+                "=cut",
+            ],
+        );
+        assert_hl(&hl, 0, 0..2, Highlight::Keyword);
+        assert_hl(&hl, 0, 3..5, Highlight::Variable);
+        assert_hl(&hl, 0, 8..12, Highlight::String);
+        assert_hl(&hl, 0, 14..23, Highlight::Comment);
+        assert_hl(&hl, 1, 3..8, Highlight::Variable);
+        assert_hl(&hl, 1, 11..22, Highlight::String);
+        assert_hl(&hl, 1, 27..29, Highlight::Variable);
+        assert_hl(&hl, 1, 32..37, Highlight::Variable);
+        assert_hl(&hl, 2, 0..5, Highlight::Builtin);
+        assert_hl(&hl, 2, 6..8, Highlight::Variable);
+        assert_hl(&hl, 2, 8..11, Highlight::Normal);
+        assert_hl(&hl, 2, 11..13, Highlight::Variable);
+        assert_hl(&hl, 2, 15..19, Highlight::String);
+        assert_hl(&hl, 2, 21..23, Highlight::Variable);
+        assert_hl(&hl, 2, 24..26, Highlight::Normal);
+        assert_hl(&hl, 3, 0..3, Highlight::Keyword);
+        assert_hl(&hl, 3, 4..9, Highlight::Definition);
+        assert_hl(&hl, 4, 0..4, Highlight::Comment);
+        assert_hl(&hl, 5, 0..12, Highlight::Comment);
+        assert_hl(&hl, 6, 0..4, Highlight::Comment);
+    }
 
-        self.eat_one(out, c, Highlight::Normal)
+    #[test]
+    fn r_syntax() {
+        let hl = highlight(
+            Language::R,
+            &[
+                "x <- function(a) a + 1 # comment",
+                "if (is.na(y)) 1L else 1e-3",
+                "z <- c(TRUE, NULL, 'a') %in% x",
+            ],
+        );
+        assert_hl(&hl, 0, 0..2, Highlight::Normal);
+        assert_hl(&hl, 0, 2..4, Highlight::Operator);
+        assert_hl(&hl, 0, 5..13, Highlight::Keyword);
+        assert_hl(&hl, 0, 19..20, Highlight::Operator);
+        assert_hl(&hl, 0, 21..22, Highlight::Number);
+        assert_hl(&hl, 0, 23..32, Highlight::Comment);
+        assert_hl(&hl, 1, 0..2, Highlight::Statement);
+        assert_hl(&hl, 1, 7..9, Highlight::Function);
+        assert_hl(&hl, 1, 14..16, Highlight::Number);
+        assert_hl(&hl, 1, 17..21, Highlight::Statement);
+        assert_hl(&hl, 1, 22..26, Highlight::Number);
+        assert_hl(&hl, 2, 5..6, Highlight::Function);
+        assert_hl(&hl, 2, 7..11, Highlight::Boolean);
+        assert_hl(&hl, 2, 13..17, Highlight::SpecialVar);
+        assert_hl(&hl, 2, 19..22, Highlight::String);
+        assert_hl(&hl, 2, 24..28, Highlight::Operator);
     }
 
-    fn highlight_line(&mut self, out: &mut [Highlight], row: &str) {
-        if self.syntax.lang == Language::Plain {
-            // On 'plain' syntax, skip highlighting since nothing is highlighted.
-            return;
-        }
+    #[test]
+    fn julia_syntax() {
+        let hl = highlight(
+            Language::Julia,
+            &[
+                "#= #= nested =# =# x = 'a' # comment",
+                "function f(x) end",
+                "println(\"x = $x\"); @time g(1_000)",
+                "\"\"\"",
+                "doc",
+                "\"\"\"",
+            ],
+        );
+        assert_hl(&hl, 0, 0..18, Highlight::Comment);
+        assert_hl(&hl, 0, 18..23, Highlight::Normal);
+        assert_hl(&hl, 0, 23..26, Highlight::Char);
+        assert_hl(&hl, 0, 27..36, Highlight::Comment);
+        assert_hl(&hl, 1, 0..8, Highlight::Keyword);
+        assert_hl(&hl, 1, 9..10, Highlight::Definition);
+        assert_hl(&hl, 1, 14..17, Highlight::Keyword);
+        assert_hl(&hl, 2, 8..13, Highlight::String);
+        assert_hl(&hl, 2, 13..15, Highlight::Variable);
+        assert_hl(&hl, 2, 15..16, Highlight::String);
+        assert_hl(&hl, 2, 19..24, Highlight::Macro);
+        assert_hl(&hl, 2, 25..26, Highlight::Function);
+        assert_hl(&hl, 2, 27..32, Highlight::Number);
+        assert_hl(&hl, 3, 0..3, Highlight::String);
+        assert_hl(&hl, 4, 0..3, Highlight::String);
+        assert_hl(&hl, 5, 0..3, Highlight::String);
+    }
 
-        // Initialize states for line highlighting
-        self.prev_hl = Highlight::Normal;
-        self.prev_char = '\0';
-        self.num = NumLit::Digit;
-        self.after_def_keyword = false;
+    #[test]
+    fn graphql_syntax() {
+        let hl = highlight(
+            Language::GraphQl,
+            &[
+                "\"\"\"",
+                "A user # not a comment",
+                "\"\"\"",
+                "type User { id: ID! } # comment",
+                "query Get($id: ID = \"x\") { user(id: $id) @skip(if: false) { name } }",
+            ],
+        );
+        assert_hl(&hl, 0, 0..3, Highlight::String);
+        assert_hl(&hl, 1, 0..22, Highlight::String);
+        assert_hl(&hl, 2, 0..3, Highlight::String);
+        assert_hl(&hl, 3, 0..4, Highlight::Keyword);
+        assert_hl(&hl, 3, 5..9, Highlight::Definition);
+        assert_hl(&hl, 3, 12..14, Highlight::Key);
+        assert_hl(&hl, 3, 16..18, Highlight::Type);
+        assert_hl(&hl, 3, 18..22, Highlight::Normal);
+        assert_hl(&hl, 3, 22..31, Highlight::Comment);
+        assert_hl(&hl, 4, 0..5, Highlight::Keyword);
+        assert_hl(&hl, 4, 6..9, Highlight::Definition);
+        assert_hl(&hl, 4, 10..13, Highlight::Variable);
+        assert_hl(&hl, 4, 15..17, Highlight::Type);
+        assert_hl(&hl, 4, 20..23, Highlight::String);
+        assert_hl(&hl, 4, 27..31, Highlight::Normal);
+        assert_hl(&hl, 4, 32..34, Highlight::Key);
+        assert_hl(&hl, 4, 36..39, Highlight::Variable);
+        assert_hl(&hl, 4, 41..46, Highlight::Attribute);
+        assert_hl(&hl, 4, 47..49, Highlight::Key);
+        assert_hl(&hl, 4, 51..56, Highlight::Boolean);
+        assert_hl(&hl, 4, 60..64, Highlight::Normal);
+    }
 
-        let mut iter = row.char_indices().enumerate();
-        while let Some((x, (idx, c))) = iter.next() {
-            let input = &row[idx..];
-            let out = &mut out[x..];
-            match self.highlight_one(c, out, input) {
-                ParseStep::Ahead(len) if len >= 2 => {
-                    // while statement always consume one character at top. Eat input chars considering that.
-                    iter.nth(len.saturating_sub(2));
-                }
-                ParseStep::Ahead(len) if len == 1 => { /* Go next */ }
-                ParseStep::Ahead(_) => unreachable!(),
-                ParseStep::Break => break,
-            }
-        }
+    #[test]
+    fn proto_syntax() {
+        let hl = highlight(
+            Language::Proto,
+            &[
+                "syntax = \"proto3\"; // comment",
+                "message Foo { int32 id = 1; repeated string tags = 0x2; }",
+                "service Api { rpc Get(Foo) returns (Foo); } /* block */",
+            ],
+        );
+        assert_hl(&hl, 0, 0..6, Highlight::Keyword);
+        assert_hl(&hl, 0, 9..17, Highlight::String);
+        assert_hl(&hl, 0, 19..29, Highlight::Comment);
+        assert_hl(&hl, 1, 0..7, Highlight::Keyword);
+        assert_hl(&hl, 1, 8..11, Highlight::Definition);
+        assert_hl(&hl, 1, 14..19, Highlight::Type);
+        assert_hl(&hl, 1, 20..25, Highlight::Normal);
+        assert_hl(&hl, 1, 25..26, Highlight::Number);
+        assert_hl(&hl, 1, 28..36, Highlight::Keyword);
+        assert_hl(&hl, 1, 37..43, Highlight::Type);
+        assert_hl(&hl, 1, 51..54, Highlight::Number);
+        assert_hl(&hl, 2, 0..7, Highlight::Keyword);
+        assert_hl(&hl, 2, 8..11, Highlight::Definition);
+        assert_hl(&hl, 2, 14..17, Highlight::Keyword);
+        assert_hl(&hl, 2, 18..21, Highlight::Definition);
+        assert_hl(&hl, 2, 27..34, Highlight::Keyword);
+        assert_hl(&hl, 2, 44..55, Highlight::Comment);
     }
-}
 
-pub struct RegionHighlight {
-    pub hl: Highlight,
-    pub start: (usize, usize),
-    pub end: (usize, usize),
-}
+    #[test]
+    fn csharp_syntax() {
+        let hl = highlight(
+            Language::CSharp,
+            &[
+                "#region Main",
+                "var p = @\"C:\\path\\\"; // comment",
+                "var s = $\"x={x} {{y}}\\n\" + @\"say \"\"hi\"\"\" + \"\\t\";",
+                "/// <summary>doc</summary>",
+                "public static async void Main() { await F(); }",
+                "var m = @\"multi",
+                "line\\\" + x;",
+            ],
+        );
+        assert_hl(&hl, 0, 0..7, Highlight::Preprocessor);
+        assert_hl(&hl, 1, 0..3, Highlight::Keyword);
+        assert_hl(&hl, 1, 8..19, Highlight::String);
+        assert_hl(&hl, 1, 19..21, Highlight::Normal);
+        assert_hl(&hl, 1, 21..31, Highlight::Comment);
+        assert_hl(&hl, 2, 8..12, Highlight::String);
+        assert_hl(&hl, 2, 12..15, Highlight::Variable);
+        assert_hl(&hl, 2, 15..21, Highlight::String);
+        assert_hl(&hl, 2, 21..23, Highlight::Escape);
+        assert_hl(&hl, 2, 23..24, Highlight::String);
+        assert_hl(&hl, 2, 25..26, Highlight::Operator);
+        assert_hl(&hl, 2, 27..40, Highlight::String);
+        assert_hl(&hl, 2, 43..44, Highlight::String);
+        assert_hl(&hl, 2, 44..46, Highlight::Escape);
+        assert_hl(&hl, 3, 0..26, Highlight::DocComment);
+        assert_hl(&hl, 4, 0..6, Highlight::Keyword);
+        assert_hl(&hl, 4, 14..19, Highlight::Keyword);
+        assert_hl(&hl, 4, 20..24, Highlight::Type);
+        assert_hl(&hl, 4, 25..29, Highlight::Function);
+        assert_hl(&hl, 4, 34..39, Highlight::Keyword);
+        assert_hl(&hl, 6, 0..6, Highlight::String);
+        assert_hl(&hl, 6, 6..7, Highlight::Normal);
+        assert_hl(&hl, 6, 7..8, Highlight::Operator);
+    }
 
-impl RegionHighlight {
-    fn contains(&self, (x, y): (usize, usize)) -> bool {
-        let ((sx, sy), (ex, ey)) = (self.start, self.end);
-        if y < sy || ey < y {
-            false
-        } else if sy < y && y < ey {
-            true
-        } else {
-            sx <= x && x < ex // Exclusive
-        }
+    #[test]
+    fn python_decorator() {
+        let hl = highlight(
+            Language::Python,
+            &[
+                "@app.route(\"/\")",
+                "def index(): return a @ b",
+                "@property",
+            ],
+        );
+        assert_hl(&hl, 0, 0..10, Highlight::Annotation);
+        assert_hl(&hl, 0, 10..11, Highlight::Normal);
+        assert_hl(&hl, 0, 11..14, Highlight::String);
+        assert_hl(&hl, 1, 20..25, Highlight::Normal);
+        assert_hl(&hl, 2, 0..9, Highlight::Annotation);
     }
-}
 
-pub struct Highlighting {
-    pub needs_update: bool,
-    // One item per render text byte
-    pub lines: Vec<Vec<Highlight>>, // TODO: One item per one character
-    previous_bottom_of_screen: usize,
-    matched: Vec<RegionHighlight>,
-    syntax: &'static SyntaxHighlight,
-}
+    #[test]
+    fn scrolling_down_highlights_only_new_lines() {
+        let mut lines = vec!["let x = 1;"; 100];
+        lines[29] = "/* open";
+        lines[40] = "close */";
+        let rows: Vec<_> = lines.iter().map(|l| Row::new(*l).unwrap()).collect();
+        let mut hl = Highlighting::new(Language::Rust, &rows);
+        hl.update(&rows, 30);
+        assert_hl(&hl, 28, 0..3, Highlight::Keyword);
 
-impl Default for Highlighting {
-    fn default() -> Self {
-        Highlighting {
-            needs_update: false,
-            lines: vec![],
-            previous_bottom_of_screen: 0,
-            matched: vec![],
-            syntax: &PLAIN_SYNTAX,
-        }
+        // Lines above the previous bottom of screen are not highlighted again
+        set_highlight(&mut hl, 0, 0, Highlight::Match);
+        set_highlight(&mut hl, 28, 0, Highlight::Match);
+        hl.update(&rows, 60);
+        assert_hl(&hl, 0, 0..1, Highlight::Match);
+        assert_hl(&hl, 28, 0..1, Highlight::Match);
+        // Highlighting continues with the state at end of the last highlighted line
+        assert_hl(&hl, 30, 0..10, Highlight::Comment);
+        assert_hl(&hl, 40, 0..8, Highlight::Comment);
+        assert_hl(&hl, 41, 0..3, Highlight::Keyword);
+        assert_hl(&hl, 59, 0..3, Highlight::Keyword);
+
+        // Scrolling up does nothing
+        set_highlight(&mut hl, 59, 0, Highlight::Match);
+        hl.update(&rows, 30);
+        assert_hl(&hl, 59, 0..1, Highlight::Match);
     }
-}
 
-impl Highlighting {
-    pub fn new(lang: Language, rows: &[Row]) -> Highlighting {
-        Highlighting {
-            needs_update: true,
-            lines: rows
-                .iter()
-                .map(|r| {
-                    iter::repeat(Highlight::Normal)
-                        .take(r.render_text().chars().count()) // TODO: One item per one character
-                        .collect()
-                })
-                .collect(),
-            previous_bottom_of_screen: 0,
-            matched: vec![],
-            syntax: SyntaxHighlight::for_lang(lang),
+    #[test]
+    fn edit_without_changing_length_highlights_same_as_fresh() {
+        let lines = ["fn f() {", "    let s = 10;", "    // comment", "}"];
+        let mut rows: Vec<_> = lines.iter().map(|l| Row::new(*l).unwrap()).collect();
+        let mut hl = Highlighting::new(Language::Rust, &rows);
+        hl.update(&rows, rows.len());
+
+        for (y, edited) in [
+            (1, "    let s = \"0;"),
+            (1, "    let s = 10;"),
+            (2, "    / comment!"),
+        ] {
+            rows[y] = Row::new(edited).unwrap();
+            hl.invalidate_line(y);
+            hl.update(&rows, rows.len());
+
+            let mut fresh = Highlighting::new(Language::Rust, &rows);
+            fresh.update(&rows, rows.len());
+            assert_eq!(
+                hl.lines, fresh.lines,
+                "after editing line {} to {:?}",
+                y, edited
+            );
         }
     }
 
-    pub fn lang_changed(&mut self, new_lang: Language) {
-        if self.syntax.lang == new_lang {
-            return;
-        }
-        self.syntax = SyntaxHighlight::for_lang(new_lang);
-        self.needs_update = true;
+    #[test]
+    fn scrolling_down_after_edit_discards_stale_states() {
+        let mut rows: Vec<_> = (0..100).map(|_| Row::new("let x = 1;").unwrap()).collect();
+        let mut hl = Highlighting::new(Language::Rust, &rows);
+        hl.update(&rows, 100);
+        hl.update(&rows, 20);
+
+        rows[5] = Row::new("/* open").unwrap();
+        hl.invalidate_line(5);
+        hl.update(&rows, 20);
+        hl.update(&rows, 40);
+        assert_hl(&hl, 19, 0..10, Highlight::Comment);
+        assert_hl(&hl, 30, 0..10, Highlight::Comment);
+        assert_hl(&hl, 39, 0..10, Highlight::Comment);
     }
 
-    fn highlight_match(&mut self, overwrite: Option<Highlight>) {
-        for region in self.matched.iter() {
-            let highlight = overwrite.unwrap_or(region.hl);
-            for y in region.start.1..=region.end.1 {
-                for (x, hl) in self.lines[y].iter_mut().enumerate() {
-                    if region.contains((x, y)) {
-                        *hl = highlight;
-                    }
-                }
-            }
-        }
+    #[test]
+    fn ini_syntax() {
+        let hl = highlight(
+            Language::Ini,
+            &[
+                "; comment = not key",
+                "[server]",
+                "port = 8080",
+                "  name: \"web\" # comment",
+                "debug=True",
+            ],
+        );
+        assert_hl(&hl, 0, 0..19, Highlight::Comment);
+        assert_hl(&hl, 1, 0..8, Highlight::Keyword);
+        assert_hl(&hl, 2, 0..4, Highlight::Key);
+        assert_hl(&hl, 2, 4..7, Highlight::Normal);
+        assert_hl(&hl, 2, 7..11, Highlight::Number);
+        assert_hl(&hl, 3, 0..2, Highlight::Normal);
+        assert_hl(&hl, 3, 2..6, Highlight::Key);
+        assert_hl(&hl, 3, 8..13, Highlight::String);
+        assert_hl(&hl, 3, 14..23, Highlight::Comment);
+        assert_hl(&hl, 4, 0..5, Highlight::Key);
+        assert_hl(&hl, 4, 6..10, Highlight::Boolean);
     }
 
-    pub fn update(&mut self, rows: &[Row], bottom_of_screen: usize) {
-        if !self.needs_update && bottom_of_screen <= self.previous_bottom_of_screen {
-            return;
-        }
+    #[test]
+    fn links_in_comments() {
+        let hl = highlight(
+            Language::Rust,
+            &[
+                "// see https://a.b/c for details",
+                "/* (http://x.y/z?q=1). */ \"https://not.link\"",
+            ],
+        );
+        assert_hl(&hl, 0, 0..7, Highlight::Comment);
+        assert_hl(&hl, 0, 7..20, Highlight::Link);
+        assert_hl(&hl, 0, 20..32, Highlight::Comment);
+        assert_hl(&hl, 1, 0..4, Highlight::Comment);
+        assert_hl(&hl, 1, 4..20, Highlight::Link);
+        assert_hl(&hl, 1, 20..25, Highlight::Comment);
+        assert_hl(&hl, 1, 26..44, Highlight::String);
 
-        let mut highlighter = Highlighter::new(&self.syntax);
+        let hl = highlight(Language::Python, &["# xhttp://a.b file://tmp/a"]);
+        assert_hl(&hl, 0, 0..14, Highlight::Comment);
+        assert_hl(&hl, 0, 14..26, Highlight::Link);
+    }
 
-        self.lines.resize_with(rows.len(), Default::default);
-        for (y, ref row) in rows.iter().enumerate().take(bottom_of_screen) {
-            let row = row.render_text();
-            self.lines[y].resize(row.chars().count(), Highlight::Normal); // TODO: One item per one character
+    #[test]
+    fn vim_script_syntax() {
+        let hl = highlight(
+            Language::VimScript,
+            &[
+                "\" comment",
+                "let g:foo = 1",
+                "  echo \"hi\" | \" comment after bar",
+                "if a || \"x\" ==# l:bar | endif",
+                "call s:Func('it''s')",
+            ],
+        );
+        assert_hl(&hl, 0, 0..9, Highlight::Comment);
+        assert_hl(&hl, 1, 0..3, Highlight::Keyword);
+        assert_hl(&hl, 1, 4..9, Highlight::Variable);
+        assert_hl(&hl, 1, 10..11, Highlight::Operator);
+        assert_hl(&hl, 1, 12..13, Highlight::Number);
+        assert_hl(&hl, 2, 2..6, Highlight::Keyword);
+        assert_hl(&hl, 2, 7..11, Highlight::String);
+        assert_hl(&hl, 2, 12..13, Highlight::Normal);
+        assert_hl(&hl, 2, 14..33, Highlight::Comment);
+        assert_hl(&hl, 3, 0..2, Highlight::Statement);
+        assert_hl(&hl, 3, 5..7, Highlight::Operator);
+        assert_hl(&hl, 3, 8..11, Highlight::String);
+        assert_hl(&hl, 3, 12..15, Highlight::Operator);
+        assert_hl(&hl, 3, 16..21, Highlight::Variable);
+        assert_hl(&hl, 3, 24..29, Highlight::Statement);
+        assert_hl(&hl, 4, 0..4, Highlight::Keyword);
+        assert_hl(&hl, 4, 5..11, Highlight::Variable);
+        assert_hl(&hl, 4, 12..19, Highlight::String);
+    }
 
-            highlighter.highlight_line(&mut self.lines[y], row);
-        }
+    #[test]
+    fn asm_syntax() {
+        let hl = highlight(
+            Language::Asm,
+            &[
+                "section .text ; comment",
+                "_start:",
+                "    mov rax, 1",
+                ".L1: movq $0x10, %RSP # comment",
+                "    .globl main",
+                "    jmp .L1",
+                "    add r0, r0, #-5",
+                "\u{3000}\u{3000}loop: nop",
+            ],
+        );
+        assert_hl(&hl, 0, 0..7, Highlight::Keyword);
+        assert_hl(&hl, 0, 8..13, Highlight::Normal);
+        assert_hl(&hl, 0, 14..23, Highlight::Comment);
+        assert_hl(&hl, 1, 0..6, Highlight::Label);
+        assert_hl(&hl, 1, 6..7, Highlight::Normal);
+        assert_hl(&hl, 2, 4..8, Highlight::Normal);
+        assert_hl(&hl, 2, 8..11, Highlight::Type);
+        assert_hl(&hl, 2, 11..13, Highlight::Normal);
+        assert_hl(&hl, 2, 13..14, Highlight::Number);
+        assert_hl(&hl, 3, 0..3, Highlight::Label);
+        assert_hl(&hl, 3, 10..15, Highlight::Number);
+        assert_hl(&hl, 3, 18..21, Highlight::Type);
+        assert_hl(&hl, 3, 22..31, Highlight::Comment);
+        assert_hl(&hl, 4, 4..10, Highlight::Keyword);
+        assert_hl(&hl, 4, 10..15, Highlight::Normal);
+        assert_hl(&hl, 5, 4..7, Highlight::Statement);
+        assert_hl(&hl, 5, 7..11, Highlight::Normal);
+        assert_hl(&hl, 6, 8..10, Highlight::Normal);
+        assert_hl(&hl, 6, 16..19, Highlight::Number);
+        assert_hl(&hl, 7, 0..2, Highlight::Normal);
+        assert_hl(&hl, 7, 2..6, Highlight::Label);
+        assert_hl(&hl, 7, 6..7, Highlight::Normal);
+    }
 
-        // Overwrite matched region
-        //
-        // TODO: Move logic to highlighter rather than overwriting highlights after.
-        // Give self.matched to Highlighter::new() and it checks each cell should be highlighted as match
-        self.highlight_match(None);
+    #[test]
+    fn statement_labels() {
+        let hl = highlight(
+            Language::C,
+            &[
+                "    if (err) goto cleanup;",
+                "cleanup: free(p);",
+                "switch (x) { case A: y = b ? c : d; default: break; }",
+            ],
+        );
+        assert_hl(&hl, 0, 13..17, Highlight::Statement);
+        assert_hl(&hl, 0, 17..18, Highlight::Normal);
+        assert_hl(&hl, 0, 18..25, Highlight::Label);
+        assert_hl(&hl, 1, 0..7, Highlight::Label);
+        assert_hl(&hl, 1, 9..13, Highlight::Function);
+        assert_hl(&hl, 2, 18..19, Highlight::Normal);
+        assert_hl(&hl, 2, 22..23, Highlight::Normal);
+        assert_hl(&hl, 2, 36..43, Highlight::Statement);
 
-        self.needs_update = false;
-        self.previous_bottom_of_screen = bottom_of_screen;
+        let hl = highlight(
+            Language::Rust,
+            &[
+                "'outer: loop {",
+                "    let s: &'static str = \"\";",
+                "    Foo { x: 1 };",
+                "    break 'outer;",
+                "    break x;",
+                "}",
+            ],
+        );
+        assert_hl(&hl, 0, 0..6, Highlight::Label);
+        assert_hl(&hl, 0, 6..7, Highlight::Normal);
+        assert_hl(&hl, 0, 8..12, Highlight::Statement);
+        assert_hl(&hl, 1, 8..9, Highlight::Definition);
+        assert_hl(&hl, 1, 12..19, Highlight::Lifetime);
+        assert_hl(&hl, 2, 10..11, Highlight::Normal);
+        assert_hl(&hl, 3, 4..9, Highlight::Statement);
+        assert_hl(&hl, 3, 10..16, Highlight::Label);
+        assert_hl(&hl, 4, 10..11, Highlight::Normal);
     }
 
-    pub fn set_matches(&mut self, matches: Vec<RegionHighlight>) {
-        self.clear_previous_match();
-        self.matched = matches;
-    }
+    #[test]
+    fn escaping_rules_per_quote() {
+        let hl = highlight(Language::Shell, &[r#"echo 'a\"b' "x\ny""#]);
+        assert_hl(&hl, 0, 5..11, Highlight::String);
+        assert_hl(&hl, 0, 12..14, Highlight::String);
+        assert_hl(&hl, 0, 14..16, Highlight::Escape);
+        assert_hl(&hl, 0, 16..18, Highlight::String);
 
-    pub fn clear_previous_match(&mut self) -> Option<usize> {
-        let dirty_start = self.matched.iter().map(|r| r.start.1).min();
-        if dirty_start.is_some() {
-            if self.syntax.lang == Language::Plain {
-                // Back to normal color. It is necessary on plain file type since it skips highlighting.
-                // Otherwise, this process is unnecessary because next highlighting will overwrite match
-                // highlights.
-                self.highlight_match(Some(Highlight::Normal));
-            }
-            self.matched.clear();
-        }
-        dirty_start
+        let syntax = SyntaxHighlightBuilder::new("toy-string-kinds")
+            .string_quote('"')
+            .string_kind(StringKind::new('\'').raw().multiline())
+            .build();
+        let lang = Highlighting::register_syntax(syntax, &["toysk"]);
+        let hl = highlight(lang, &[r#""a\n" 'b\n"#, "c' x"]);
+        assert_hl(&hl, 0, 1..2, Highlight::String);
+        assert_hl(&hl, 0, 2..4, Highlight::Escape);
+        assert_hl(&hl, 0, 6..10, Highlight::String);
+        assert_hl(&hl, 1, 0..2, Highlight::String);
+        assert_hl(&hl, 1, 2..4, Highlight::Normal);
     }
 }
@@ -26,7 +26,7 @@ impl History {
             return false; // Do nothing when no change was added
         }
 
-        let diffs = mem::replace(&mut self.ongoing, vec![]);
+        let diffs = mem::take(&mut self.ongoing);
 
         if self.entries.len() == MAX_ENTRIES {
             self.entries.pop_front();
@@ -47,32 +47,35 @@ impl History {
         diffs: I,
         which: UndoRedo,
         rows: &mut Vec<Row>,
-    ) -> (usize, usize, usize) {
-        diffs.fold((0, 0, usize::max_value()), |(_, _, dirty_start), diff| {
-            let (x, y) = diff.apply(rows, which);
-            (x, y, cmp::min(dirty_start, y))
-        })
+    ) -> (usize, usize, usize, usize) {
+        diffs.fold(
+            (0, 0, usize::MAX, 0),
+            |(_, _, dirty_start, dirty_end), diff| {
+                let (x, y) = diff.apply(rows, which);
+                (x, y, cmp::min(dirty_start, y), cmp::max(dirty_end, y))
+            },
+        )
     }
 
-    pub fn undo(&mut self, rows: &mut Vec<Row>) -> Option<(usize, usize, usize, bool)> {
+    pub fn undo(&mut self, rows: &mut Vec<Row>) -> Option<(usize, usize, usize, usize, bool)> {
         let edited = self.finish_ongoing_edit();
         if self.index == 0 {
             return None;
         }
         self.index -= 1;
         let i = self.entries[self.index].iter().rev();
-        let (x, y, dirty_start) = Self::apply_diffs(i, UndoRedo::Undo, rows);
-        Some((x, y, dirty_start, edited))
+        let (x, y, dirty_start, dirty_end) = Self::apply_diffs(i, UndoRedo::Undo, rows);
+        Some((x, y, dirty_start, dirty_end, edited))
     }
 
-    pub fn redo(&mut self, rows: &mut Vec<Row>) -> Option<(usize, usize, usize, bool)> {
+    pub fn redo(&mut self, rows: &mut Vec<Row>) -> Option<(usize, usize, usize, usize, bool)> {
         let edited = self.finish_ongoing_edit();
         if self.index == self.entries.len() {
             return None;
         }
         self.index += 1;
         let i = self.entries[self.index - 1].iter();
-        let (x, y, dirty_start) = Self::apply_diffs(i, UndoRedo::Redo, rows);
-        Some((x, y, dirty_start, edited))
+        let (x, y, dirty_start, dirty_end) = Self::apply_diffs(i, UndoRedo::Redo, rows);
+        Some((x, y, dirty_start, dirty_end, edited))
     }
 }
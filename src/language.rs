@@ -1,3 +1,4 @@
+use crate::syntax_file;
 use std::ffi::OsStr;
 use std::path::Path;
 
@@ -6,62 +7,522 @@ pub enum Indent {
     Fixed(&'static str),
 }
 
+// Number of lines scanned by Language::detect_from_content()
+pub const DETECT_CONTENT_LINES: usize = 30;
+
+// Number of lines at start and end of file scanned for modelines. This is the same as default of
+// 'modelines' option of Vim
+pub const MODELINE_LINES: usize = 5;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Language {
     Plain,
     C,
     Rust,
     JavaScript,
+    TypeScript,
     Go,
     Cpp,
     Python,
+    Ruby,
+    Css,
+    Html,
+    Shell,
+    Sql,
+    Json,
+    Yaml,
+    Markdown,
+    Php,
+    Lua,
+    Haskell,
+    Kotlin,
+    Swift,
+    Java,
+    Toml,
+    Elixir,
+    OCaml,
+    Nim,
+    Zig,
+    Scala,
+    Makefile,
+    Perl,
+    R,
+    Julia,
+    GraphQl,
+    Proto,
+    CSharp,
+    Ini,
+    VimScript,
+    Asm,
+    // Language loaded from syntax definition file at runtime
+    Custom(&'static str),
 }
 
 impl Language {
     pub fn name(self) -> &'static str {
         use Language::*;
         match self {
+            Custom(name) => name,
             Plain => "plain",
             C => "c",
             Rust => "rust",
             JavaScript => "javascript",
+            TypeScript => "typescript",
             Go => "go",
             Cpp => "c++",
             Python => "python",
+            Ruby => "ruby",
+            Css => "css",
+            Html => "html",
+            Shell => "shell",
+            Sql => "sql",
+            Json => "json",
+            Yaml => "yaml",
+            Markdown => "markdown",
+            Php => "php",
+            Lua => "lua",
+            Haskell => "haskell",
+            Kotlin => "kotlin",
+            Swift => "swift",
+            Java => "java",
+            Toml => "toml",
+            Elixir => "elixir",
+            OCaml => "ocaml",
+            Nim => "nim",
+            Zig => "zig",
+            Scala => "scala",
+            Makefile => "makefile",
+            Perl => "perl",
+            R => "r",
+            Julia => "julia",
+            GraphQl => "graphql",
+            Proto => "proto",
+            CSharp => "csharp",
+            Ini => "ini",
+            VimScript => "vim",
+            Asm => "asm",
         }
     }
 
     fn file_exts(self) -> &'static [&'static str] {
         use Language::*;
         match self {
-            Plain => &[],
+            Custom(_) | Plain => &[],
             C => &["c", "h"],
             Rust => &["rs"],
-            JavaScript => &["js"],
+            JavaScript => &["js", "mjs", "cjs"],
+            TypeScript => &["ts", "tsx"],
             Go => &["go"],
             Cpp => &["cpp", "hpp", "cxx", "hxx", "cc", "hh"],
             Python => &["py"],
+            Ruby => &["rb", "rake", "gemspec"],
+            Css => &["css"],
+            Html => &["html", "htm"],
+            Shell => &["sh", "bash"],
+            Sql => &["sql"],
+            Json => &["json"],
+            Yaml => &["yml", "yaml"],
+            Markdown => &["md", "markdown"],
+            Php => &["php"],
+            Lua => &["lua"],
+            Haskell => &["hs"],
+            Kotlin => &["kt", "kts"],
+            Swift => &["swift"],
+            Java => &["java"],
+            Toml => &["toml"],
+            Elixir => &["ex", "exs"],
+            OCaml => &["ml", "mli"],
+            Nim => &["nim", "nims", "nimble"],
+            Zig => &["zig", "zon"],
+            Scala => &["scala", "sc", "sbt"],
+            Makefile => &["mk", "mak"],
+            Perl => &["pl", "pm", "t"],
+            R => &["r", "R"],
+            Julia => &["jl"],
+            GraphQl => &["graphql", "graphqls", "gql"],
+            Proto => &["proto"],
+            CSharp => &["cs", "csx"],
+            Ini => &["ini", "cfg", "properties"],
+            VimScript => &["vim"],
+            Asm => &["asm", "s", "S", "nasm"],
         }
     }
 
     pub fn indent(self) -> Indent {
         use Language::*;
         match self {
-            Plain | Go => Indent::AsIs,
-            C | Rust | Cpp | Python => Indent::Fixed("    "),
-            JavaScript => Indent::Fixed("  "),
+            Custom(_) | Plain | Go | Markdown | Toml | Makefile | Ini | Asm => Indent::AsIs,
+            C | Rust | Cpp | Python | Php | Kotlin | Swift | Java | Zig | Perl | Julia | CSharp => {
+                Indent::Fixed("    ")
+            }
+            JavaScript | TypeScript | Ruby | Css | Html | Shell | Sql | Json | Yaml | Lua
+            | Haskell | Elixir | OCaml | Nim | Scala | R | GraphQl | Proto | VimScript => {
+                Indent::Fixed("  ")
+            }
         }
     }
 
     pub fn detect<P: AsRef<Path>>(path: P) -> Language {
         use Language::*;
-        if let Some(ext) = path.as_ref().extension().and_then(OsStr::to_str) {
-            for lang in &[C, Rust, JavaScript, Go, Cpp, Python] {
+        let path = path.as_ref();
+        // Some files are named without extension
+        match path.file_name().and_then(OsStr::to_str) {
+            Some("Makefile" | "makefile" | "GNUmakefile") => return Makefile,
+            Some(".vimrc" | "_vimrc" | ".gvimrc" | ".exrc") => return VimScript,
+            _ => {}
+        }
+        if let Some(ext) = path.extension().and_then(OsStr::to_str) {
+            for lang in &[
+                C, Rust, JavaScript, TypeScript, Go, Cpp, Python, Ruby, Css, Html, Shell, Sql,
+                Json, Yaml, Markdown, Php, Lua, Haskell, Kotlin, Swift, Java, Toml, Elixir, OCaml,
+                Nim, Zig, Scala, Makefile, Perl, R, Julia, GraphQl, Proto, CSharp, Ini, VimScript,
+                Asm,
+            ] {
                 if lang.file_exts().contains(&ext) {
                     return *lang;
                 }
             }
+            if let Some(lang) = syntax_file::find_lang_by_ext(ext) {
+                return lang;
+            }
         }
         Plain
     }
+
+    // Language of a file path such as the new path given on 'save as'
+    pub fn from_file_path(path: &Path) -> Language {
+        Language::detect(path)
+    }
+
+    // Detect language from interpreter in shebang line such as '#!/usr/bin/env python3'
+    pub fn detect_from_shebang(first_line: &str) -> Option<Language> {
+        use Language::*;
+        let mut words = first_line.strip_prefix("#!")?.split_whitespace();
+        let mut cmd = words.next()?.rsplit('/').next()?;
+        if cmd == "env" {
+            // Skip options of env like '-S'
+            cmd = words.find(|w| !w.starts_with('-'))?;
+        }
+        // Strip version like 'python3.8'
+        match cmd.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.') {
+            "python" => Some(Python),
+            "sh" | "bash" | "zsh" | "dash" | "ksh" => Some(Shell),
+            "node" | "nodejs" => Some(JavaScript),
+            "ts-node" | "deno" => Some(TypeScript),
+            "ruby" => Some(Ruby),
+            "php" => Some(Php),
+            "lua" | "luajit" => Some(Lua),
+            "runhaskell" | "runghc" => Some(Haskell),
+            "swift" => Some(Swift),
+            "elixir" => Some(Elixir),
+            "ocaml" => Some(OCaml),
+            "nim" => Some(Nim),
+            "scala" => Some(Scala),
+            "perl" => Some(Perl),
+            "Rscript" => Some(R),
+            "julia" => Some(Julia),
+            _ => None,
+        }
+    }
+
+    // Detect language from Vim modeline such as 'vim: set ft=python:' or Emacs file variables such
+    // as '-*- mode: python -*-'. `lines` should be the first and last lines of the file
+    pub fn detect_from_modeline(lines: &[&str]) -> Option<Language> {
+        lines
+            .iter()
+            .find_map(|l| vim_file_type(l).or_else(|| emacs_mode(l)))
+            .and_then(Language::from_type_name)
+    }
+
+    // Map file type name of Vim or major mode name of Emacs to language
+    fn from_type_name(name: &str) -> Option<Language> {
+        use Language::*;
+        let name = name.to_ascii_lowercase();
+        let name = name.strip_suffix("-mode").unwrap_or(&name);
+        let lang = match name {
+            "c" => C,
+            "rust" => Rust,
+            "javascript" | "js" | "js2" => JavaScript,
+            "typescript" | "ts" => TypeScript,
+            "go" => Go,
+            "cpp" | "c++" => Cpp,
+            "python" | "py" => Python,
+            "ruby" => Ruby,
+            "css" => Css,
+            "html" => Html,
+            "sh" | "bash" | "zsh" | "shell-script" => Shell,
+            "sql" => Sql,
+            "json" => Json,
+            "yaml" => Yaml,
+            "markdown" | "md" => Markdown,
+            "php" => Php,
+            "lua" => Lua,
+            "haskell" => Haskell,
+            "kotlin" => Kotlin,
+            "swift" => Swift,
+            "java" => Java,
+            "toml" | "conf-toml" => Toml,
+            "elixir" => Elixir,
+            "ocaml" | "tuareg" => OCaml,
+            "nim" => Nim,
+            "zig" => Zig,
+            "scala" => Scala,
+            "make" | "makefile" | "makefile-gmake" => Makefile,
+            "perl" | "cperl" => Perl,
+            "r" | "ess-r" => R,
+            "julia" => Julia,
+            "graphql" | "gql" => GraphQl,
+            "proto" | "protobuf" => Proto,
+            "cs" | "csharp" => CSharp,
+            "ini" | "dosini" | "conf" | "properties" | "jproperties" => Ini,
+            "vim" => VimScript,
+            "asm" | "nasm" | "gas" => Asm,
+            "text" | "fundamental" => Plain,
+            _ => return syntax_file::find_syntax(name).map(|s| s.lang()),
+        };
+        Some(lang)
+    }
+
+    // Guess language from the first lines of text. This returns None unless exactly one
+    // language matches since the heuristics are rough
+    pub fn detect_from_content(text: &str) -> Option<Language> {
+        use Language::*;
+        let (mut rust_main, mut rust_let, mut python, mut go, mut html) =
+            (false, false, false, false, false);
+        for line in text.lines().take(DETECT_CONTENT_LINES) {
+            let line = line.trim();
+            rust_main |= line.starts_with("fn main()");
+            rust_let |= line.starts_with("let ");
+            python |= line.starts_with("def ") && line.ends_with(':');
+            go |= line == "package main";
+            html |= line
+                .get(..14)
+                .is_some_and(|s| s.eq_ignore_ascii_case("<!DOCTYPE html"));
+        }
+
+        let mut found = None;
+        for (matched, lang) in &[
+            (rust_main && rust_let, Rust),
+            (python, Python),
+            (go, Go),
+            (html, Html),
+        ] {
+            if *matched {
+                if found.is_some() {
+                    return None; // Ambiguous
+                }
+                found = Some(*lang);
+            }
+        }
+        found
+    }
+}
+
+// Returns value of 'filetype' option in Vim modeline such as '# vim: set ft=python:' or
+// '// vim: ts=4 filetype=c'
+fn vim_file_type(line: &str) -> Option<&str> {
+    for marker in &["vim:", "vi:", "ex:"] {
+        if let Some(idx) = line.find(marker) {
+            // Modeline must be preceded by whitespace or start of line
+            if idx > 0 && !line[..idx].ends_with(char::is_whitespace) {
+                continue;
+            }
+            return line[idx + marker.len()..]
+                .split(|c: char| c.is_whitespace() || c == ':')
+                .find_map(|opt| {
+                    opt.strip_prefix("ft=")
+                        .or_else(|| opt.strip_prefix("filetype="))
+                })
+                .filter(|ft| !ft.is_empty());
+        }
+    }
+    None
+}
+
+// Returns major mode in Emacs file variables such as '-*- mode: python; coding: utf-8 -*-' or
+// '-*- python -*-'
+fn emacs_mode(line: &str) -> Option<&str> {
+    let start = line.find("-*-")? + 3;
+    let len = line[start..].find("-*-")?;
+    let vars = line[start..start + len].trim();
+    if !vars.contains(':') {
+        return Some(vars).filter(|v| !v.is_empty());
+    }
+    vars.split(';').find_map(|var| {
+        let (name, value) = var.split_once(':')?;
+        if name.trim().eq_ignore_ascii_case("mode") {
+            Some(value.trim())
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_shebang() {
+        for (line, expected) in &[
+            ("#!/bin/sh", Some(Language::Shell)),
+            ("#!/bin/bash -e", Some(Language::Shell)),
+            ("#! /usr/bin/python", Some(Language::Python)),
+            ("#!/usr/bin/env python3", Some(Language::Python)),
+            ("#!/usr/bin/env python3.8", Some(Language::Python)),
+            (
+                "#!/usr/bin/env -S node --harmony",
+                Some(Language::JavaScript),
+            ),
+            ("#!/usr/local/bin/ruby -w", Some(Language::Ruby)),
+            ("#!/usr/bin/env elixir", Some(Language::Elixir)),
+            ("#!/usr/bin/env", None),
+            ("#!/usr/bin/perl -w", Some(Language::Perl)),
+            ("#!/usr/bin/env Rscript", Some(Language::R)),
+            ("#!/usr/bin/awk -f", None),
+            ("# python", None),
+            ("import os", None),
+            ("", None),
+        ] {
+            assert_eq!(Language::detect_from_shebang(line), *expected, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn detect_modeline() {
+        for (lines, expected) in &[
+            (&["# vim: set ft=python:"][..], Some(Language::Python)),
+            (&["// vim: ts=4 filetype=c"], Some(Language::C)),
+            (
+                &["/* vi:set sw=2 ft=javascript: */"],
+                Some(Language::JavaScript),
+            ),
+            (&["foo", "", "# vim: ft=sh"], Some(Language::Shell)),
+            (
+                &["#!/usr/bin/env foo", "# -*- mode: python -*-"],
+                Some(Language::Python),
+            ),
+            (
+                &[";; -*- Mode: Ruby; coding: utf-8 -*-"],
+                Some(Language::Ruby),
+            ),
+            (&["// -*- c++ -*-"], Some(Language::Cpp)),
+            (&["# vim: set ft=brainfuck:"], None),
+            (&["-*- mode: unknown-lang -*-"], None),
+            (&["regex: ft=python"], None),
+            (&["# vim: set ts=8:"], None),
+            (&["-*- coding: utf-8 -*-"], None),
+            (&["hello"], None),
+            (&[], None),
+        ] {
+            assert_eq!(
+                Language::detect_from_modeline(lines),
+                *expected,
+                "{:?}",
+                lines
+            );
+        }
+    }
+
+    #[test]
+    fn detect_content() {
+        for (text, expected) in &[
+            ("fn main() {\n    let x = 1;\n}", Some(Language::Rust)),
+            ("import os\n\ndef main():\n    pass", Some(Language::Python)),
+            ("package main\n\nfunc main() {}", Some(Language::Go)),
+            ("<!doctype html>\n<html></html>", Some(Language::Html)),
+            ("fn main() {}", None),
+            ("def foo\n  1\nend", None),
+            ("package main\ndef f():", None),
+            ("hello, world", None),
+            ("あいうえおか", None),
+            ("", None),
+        ] {
+            assert_eq!(Language::detect_from_content(text), *expected, "{:?}", text);
+        }
+
+        let far = format!("{}package main", "\n".repeat(DETECT_CONTENT_LINES));
+        assert_eq!(Language::detect_from_content(&far), None);
+    }
+
+    #[test]
+    fn detect_file_extension() {
+        for (path, expected) in &[
+            ("main.rs", Language::Rust),
+            ("foo.c", Language::C),
+            ("include/foo.h", Language::C),
+            ("index.js", Language::JavaScript),
+            ("module.mjs", Language::JavaScript),
+            ("main.go", Language::Go),
+            ("app.tsx", Language::TypeScript),
+            ("a.hpp", Language::Cpp),
+            ("setup.py", Language::Python),
+            ("Rakefile.rake", Language::Ruby),
+            ("style.css", Language::Css),
+            ("index.htm", Language::Html),
+            ("build.sh", Language::Shell),
+            ("schema.sql", Language::Sql),
+            ("package.json", Language::Json),
+            (".travis.yml", Language::Yaml),
+            ("README.md", Language::Markdown),
+            ("index.php", Language::Php),
+            ("init.lua", Language::Lua),
+            ("Main.hs", Language::Haskell),
+            ("build.gradle.kts", Language::Kotlin),
+            ("main.swift", Language::Swift),
+            ("Main.java", Language::Java),
+            ("Cargo.toml", Language::Toml),
+            ("lib/app.ex", Language::Elixir),
+            ("mix.exs", Language::Elixir),
+            ("main.ml", Language::OCaml),
+            ("parser.mli", Language::OCaml),
+            ("main.nim", Language::Nim),
+            ("pkg.nimble", Language::Nim),
+            ("build.zig", Language::Zig),
+            ("Main.scala", Language::Scala),
+            ("build.sbt", Language::Scala),
+            ("Makefile", Language::Makefile),
+            ("src/GNUmakefile", Language::Makefile),
+            ("rules.mk", Language::Makefile),
+            ("script.pl", Language::Perl),
+            ("lib/Foo/Bar.pm", Language::Perl),
+            ("plot.R", Language::R),
+            ("main.jl", Language::Julia),
+            ("schema.graphql", Language::GraphQl),
+            ("api/user.proto", Language::Proto),
+            ("Program.cs", Language::CSharp),
+            ("setup.cfg", Language::Ini),
+            ("app.properties", Language::Ini),
+            ("plugin/foo.vim", Language::VimScript),
+            ("/home/u/.vimrc", Language::VimScript),
+            ("boot.asm", Language::Asm),
+            ("start.S", Language::Asm),
+            ("foo.unknown", Language::Plain),
+            ("Dockerfile", Language::Plain),
+            ("rs", Language::Plain),
+            ("", Language::Plain),
+        ] {
+            assert_eq!(Language::detect(path), *expected, "{:?}", path);
+        }
+    }
+
+    #[test]
+    fn language_from_file_path() {
+        for (path, expected) in &[
+            ("main.rs", Language::Rust),
+            ("foo.c", Language::C),
+            ("foo.h", Language::C),
+            ("index.js", Language::JavaScript),
+            ("module.mjs", Language::JavaScript),
+            ("main.go", Language::Go),
+            ("foo.unknown", Language::Plain),
+        ] {
+            assert_eq!(
+                Language::from_file_path(Path::new(path)),
+                *expected,
+                "{:?}",
+                path
+            );
+        }
+    }
 }
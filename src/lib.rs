@@ -11,6 +11,7 @@
 mod edit_diff;
 mod editor;
 mod error;
+mod export;
 mod highlight;
 mod history;
 mod input;
@@ -18,14 +19,19 @@ mod language;
 mod prompt;
 mod row;
 mod screen;
+mod search;
 mod signal;
 mod status_bar;
+mod syntax_file;
 mod term_color;
 mod text_buffer;
 
 pub use editor::Editor;
 pub use error::{Error, Result};
+pub use highlight::{Highlighting, StringKind, SyntaxHighlight, SyntaxHighlightBuilder};
 pub use input::{InputSeq, KeySeq, StdinRawMode};
 pub use language::Language;
 pub use screen::{Screen, HELP, VERSION};
+pub use search::{find_matches, Regex};
+pub use syntax_file::load_syntax_file;
 pub use text_buffer::{Lines, TextBuffer};
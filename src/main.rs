@@ -43,6 +43,12 @@ fn main() {
     let mut opts = Options::new();
     opts.optflag("v", "version", "Print version");
     opts.optflag("h", "help", "Print this help");
+    opts.optmulti(
+        "s",
+        "syntax",
+        "Load syntax definition from TOML file",
+        "FILE",
+    );
 
     let matches = match opts.parse(argv) {
         Ok(m) => m,
@@ -62,6 +68,13 @@ fn main() {
         return;
     }
 
+    for path in matches.opt_strs("s") {
+        if let Err(err) = kiro::load_syntax_file(&path) {
+            eprintln!("Error: Could not load syntax file {}: {}", path, err);
+            exit(1);
+        }
+    }
+
     if let Err(err) = edit(matches.free) {
         eprintln!("Error: {}", err);
         exit(1);
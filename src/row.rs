@@ -1,4 +1,5 @@
 use crate::error::{Error, Result};
+use std::cmp;
 use std::ops;
 use unicode_width::UnicodeWidthChar;
 
@@ -132,6 +133,21 @@ impl Row {
         })
     }
 
+    // Index of character in render text corresponding to character at `cx`. They differ when tabs
+    // are expanded to spaces
+    pub fn render_idx_of(&self, cx: usize) -> usize {
+        let cx = cmp::min(cx, self.len());
+        let (_, idx) = self[..cx].chars().fold((0, 0), |(rx, idx), ch| {
+            if ch == '\t' {
+                let spaces = TAB_STOP - (rx % TAB_STOP);
+                (rx + spaces, idx + spaces)
+            } else {
+                (rx + ch.width_cjk().unwrap(), idx + 1)
+            }
+        });
+        idx
+    }
+
     pub fn insert_char(&mut self, at: usize, c: char) {
         if self.len() <= at {
             self.buf.push(c);
@@ -4,7 +4,7 @@ use crate::input::{InputSeq, KeySeq};
 use crate::row::Row;
 use crate::signal::SigwinchWatcher;
 use crate::status_bar::StatusBar;
-use crate::term_color::{Color, TermColor};
+use crate::term_color::{Color, Style, TermColor};
 use crate::text_buffer::TextBuffer;
 use std::cmp;
 use std::io::Write;
@@ -200,7 +200,7 @@ impl<W: Write> Screen<W> {
     fn draw_status_bar<B: Write>(&self, mut buf: B, status_bar: &StatusBar) -> Result<()> {
         write!(buf, "\x1b[{}H", self.rows() + 1)?;
 
-        buf.write(self.term_color.sequence(Color::Invert))?;
+        buf.write(&self.term_color.sequence(Color::Invert))?;
 
         let left = status_bar.left();
         // TODO: Handle multi-byte chars correctly
@@ -209,7 +209,7 @@ impl<W: Write> Screen<W> {
 
         let rest_len = self.num_cols - left.len();
         if rest_len == 0 {
-            buf.write(self.term_color.sequence(Color::Reset))?;
+            buf.write(&self.term_color.sequence(Color::Reset))?;
             return Ok(());
         }
 
@@ -218,7 +218,7 @@ impl<W: Write> Screen<W> {
             for _ in 0..rest_len {
                 buf.write(b" ")?;
             }
-            buf.write(self.term_color.sequence(Color::Reset))?;
+            buf.write(&self.term_color.sequence(Color::Reset))?;
             return Ok(());
         }
 
@@ -227,7 +227,7 @@ impl<W: Write> Screen<W> {
         }
         buf.write(right.as_bytes())?;
 
-        buf.write(self.term_color.sequence(Color::Reset))?;
+        buf.write(&self.term_color.sequence(Color::Reset))?;
         Ok(())
     }
 
@@ -238,13 +238,13 @@ impl<W: Write> Screen<W> {
         write!(buf, "\x1b[{}H", self.num_rows + 2)?;
 
         if message.kind == StatusMessageKind::Error {
-            buf.write(self.term_color.sequence(Color::RedBG))?;
+            buf.write(&self.term_color.sequence(Color::RedBG))?;
         }
 
         buf.write(text.as_bytes())?;
 
         if message.kind != StatusMessageKind::Info {
-            buf.write(self.term_color.sequence(Color::Reset))?;
+            buf.write(&self.term_color.sequence(Color::Reset))?;
         }
 
         buf.write(b"\x1b[K")?;
@@ -255,7 +255,7 @@ impl<W: Write> Screen<W> {
         self.write_flush(b"\x1b[?25l")?; // Hide cursor
 
         let mut buf = Vec::with_capacity((self.rows() + 2 + self.num_cols) * 3);
-        buf.write(self.term_color.sequence(Color::Reset))?;
+        buf.write(&self.term_color.sequence(Color::Reset))?;
 
         for y in 0..self.rows() {
             write!(buf, "\x1b[{}H", y + 1)?;
@@ -265,23 +265,23 @@ impl<W: Write> Screen<W> {
                 let welcome = self.trim_line(&msg_buf);
                 let padding = (self.num_cols - welcome.len()) / 2;
                 if padding > 0 {
-                    buf.write(self.term_color.sequence(Color::NonText))?;
+                    buf.write(&self.term_color.sequence(Color::NonText))?;
                     buf.write(b"~")?;
-                    buf.write(self.term_color.sequence(Color::Reset))?;
+                    buf.write(&self.term_color.sequence(Color::Reset))?;
                     for _ in 0..padding - 1 {
                         buf.write(b" ")?;
                     }
                 }
                 buf.write(welcome.as_bytes())?;
             } else {
-                buf.write(self.term_color.sequence(Color::NonText))?;
+                buf.write(&self.term_color.sequence(Color::NonText))?;
                 buf.write(b"~")?;
             }
 
             buf.write(b"\x1b[K")?;
         }
 
-        buf.write(self.term_color.sequence(Color::Reset))?;
+        buf.write(&self.term_color.sequence(Color::Reset))?;
         self.draw_status_bar(&mut buf, status_bar)?;
         if let Some(message) = &self.message {
             self.draw_message_bar(&mut buf, message)?;
@@ -304,7 +304,7 @@ impl<W: Write> Screen<W> {
     ) -> Result<()> {
         let row_len = rows.len();
 
-        buf.write(self.term_color.sequence(Color::Reset))?;
+        buf.write(&self.term_color.sequence(Color::Reset))?;
 
         for y in 0..self.rows() {
             let file_row = y + self.rowoff;
@@ -317,14 +317,14 @@ impl<W: Write> Screen<W> {
             write!(buf, "\x1b[{}H", y + 1)?;
 
             if file_row >= row_len {
-                buf.write(self.term_color.sequence(Color::NonText))?;
+                buf.write(&self.term_color.sequence(Color::NonText))?;
                 buf.write(b"~")?;
             } else {
                 let row = &rows[file_row];
 
                 let mut col = 0;
-                let mut prev_color = Color::Reset;
-                for (c, hl) in row.render_text().chars().zip(hl.lines[file_row].iter()) {
+                let mut prev_style = Style::from(Color::Reset);
+                for (c, h) in row.render_text().chars().zip(hl.line(file_row)) {
                     col += c.width_cjk().unwrap_or(1);
                     if col <= self.coloff {
                         continue;
@@ -332,13 +332,10 @@ impl<W: Write> Screen<W> {
                         break;
                     }
 
-                    let color = hl.color();
-                    if color != prev_color {
-                        if prev_color.has_bg_color() {
-                            buf.write(self.term_color.sequence(Color::Reset))?;
-                        }
-                        buf.write(self.term_color.sequence(color))?;
-                        prev_color = color;
+                    let style = hl.color_scheme().style(h);
+                    if style != prev_style {
+                        buf.write(&self.term_color.switch_sequence(prev_style, style))?;
+                        prev_style = style;
                     }
 
                     write!(buf, "{}", c)?;
@@ -347,7 +344,7 @@ impl<W: Write> Screen<W> {
 
             // Ensure to end with reset color sequence. Otherwise, when background color is highlighted
             // at the end of line, highlight will continue to the end of last column in terminal window.
-            buf.write(self.term_color.sequence(Color::Reset))?;
+            buf.write(&self.term_color.sequence(Color::Reset))?;
 
             // Erases the part of the line to the right of the cursor. http://vt100.net/docs/vt100-ug/chapter3.html#EL
             buf.write(b"\x1b[K")?;
@@ -529,12 +526,12 @@ impl<W: Write> Screen<W> {
             buf.write(left_pad.as_bytes())?;
 
             let help = &help[idx][..cmp::min(help[idx].len(), self.num_cols)];
-            buf.write(self.term_color.sequence(Color::Cyan))?;
+            buf.write(&self.term_color.sequence(Color::Cyan))?;
             let mut cols = help.split(':');
             if let Some(col) = cols.next() {
                 buf.write(col.as_bytes())?;
             }
-            buf.write(self.term_color.sequence(Color::Reset))?;
+            buf.write(&self.term_color.sequence(Color::Reset))?;
             if let Some(col) = cols.next() {
                 write!(buf, ":{}", col)?;
             }
@@ -616,6 +613,10 @@ impl<W: Write> Screen<W> {
         self.num_cols
     }
 
+    pub fn term_color(&self) -> TermColor {
+        self.term_color
+    }
+
     pub fn message_text(&self) -> &'_ str {
         self.message.as_ref().map(|m| m.text.as_str()).unwrap_or("")
     }
@@ -0,0 +1,330 @@
+// Regular expression search over lines. Only a small subset of regular expression syntax is
+// supported since the editor has no dependency on regex engine:
+//
+//   - literal characters and escaped metacharacters like '\.'
+//   - '.', '\w', '\W', '\d', '\D', '\s', '\S' and character classes like '[a-z_]' or '[^0-9]'
+//   - quantifiers '*', '+' and '?' (greedy)
+//   - anchors '^', '$' and word boundary '\b'
+//
+// Groups and alternation are not supported. Matches never span multiple lines.
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy)]
+enum ClassItem {
+    Range(char, char),
+    Word,
+    Digit,
+    Space,
+}
+
+impl ClassItem {
+    fn contains(self, c: char) -> bool {
+        match self {
+            ClassItem::Range(start, end) => start <= c && c <= end,
+            ClassItem::Word => is_word_char(c),
+            ClassItem::Digit => c.is_ascii_digit(),
+            ClassItem::Space => c.is_whitespace(),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Atom {
+    Char(char),
+    Any,
+    Class(Vec<ClassItem>, bool), // Second value is true when the class is negated
+}
+
+impl Atom {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Atom::Char(expected) => *expected == c,
+            Atom::Any => true,
+            Atom::Class(items, negated) => items.iter().any(|i| i.contains(c)) != *negated,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    Repeat(Atom, usize, usize), // Atom repeated from min to max times
+    LineStart,
+    LineEnd,
+    WordBoundary,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn escaped_class(c: char) -> Option<(ClassItem, bool)> {
+    match c {
+        'w' => Some((ClassItem::Word, false)),
+        'W' => Some((ClassItem::Word, true)),
+        'd' => Some((ClassItem::Digit, false)),
+        'D' => Some((ClassItem::Digit, true)),
+        's' => Some((ClassItem::Space, false)),
+        'S' => Some((ClassItem::Space, true)),
+        _ => None,
+    }
+}
+
+#[derive(Debug)]
+pub struct Regex {
+    nodes: Vec<Node>,
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Regex> {
+        let error = |msg: &str| Err(Error::InvalidRegex(format!("{}: {:?}", msg, pattern)));
+        let mut nodes = vec![];
+        let mut chars = pattern.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            let atom = match c {
+                '^' => {
+                    nodes.push(Node::LineStart);
+                    continue;
+                }
+                '$' => {
+                    nodes.push(Node::LineEnd);
+                    continue;
+                }
+                '*' | '+' | '?' => {
+                    let (min, max) = match c {
+                        '*' => (0, usize::MAX),
+                        '+' => (1, usize::MAX),
+                        _ => (0, 1),
+                    };
+                    match nodes.last_mut() {
+                        Some(Node::Repeat(_, m, n)) if *m == 1 && *n == 1 => {
+                            *m = min;
+                            *n = max;
+                        }
+                        _ => return error("nothing to repeat"),
+                    }
+                    continue;
+                }
+                '(' | ')' | '|' | '{' | '}' => {
+                    return error("groups, alternation and counted repetition are not supported")
+                }
+                '.' => Atom::Any,
+                '\\' => match chars.next() {
+                    Some('b') => {
+                        nodes.push(Node::WordBoundary);
+                        continue;
+                    }
+                    Some('t') => Atom::Char('\t'),
+                    Some(c) => match escaped_class(c) {
+                        Some((item, negated)) => Atom::Class(vec![item], negated),
+                        None if c.is_alphanumeric() => return error("unknown escape sequence"),
+                        None => Atom::Char(c),
+                    },
+                    None => return error("pattern ends with '\\'"),
+                },
+                '[' => {
+                    let negated = chars.next_if_eq(&'^').is_some();
+                    let mut items = vec![];
+                    loop {
+                        let start = match chars.next() {
+                            Some(']') if !items.is_empty() => break,
+                            Some('\\') => match chars.next() {
+                                Some(c) => match escaped_class(c) {
+                                    Some((item, false)) => {
+                                        items.push(item);
+                                        continue;
+                                    }
+                                    Some((_, true)) => {
+                                        return error("negated class in character class")
+                                    }
+                                    None => c,
+                                },
+                                None => return error("character class is not closed"),
+                            },
+                            Some(c) => c,
+                            None => return error("character class is not closed"),
+                        };
+                        let end = if chars.peek() == Some(&'-') {
+                            chars.next();
+                            match chars.next() {
+                                Some(']') => {
+                                    // '-' at end of class is a literal
+                                    items.push(ClassItem::Range(start, start));
+                                    items.push(ClassItem::Range('-', '-'));
+                                    break;
+                                }
+                                Some(end) if start <= end => end,
+                                Some(_) => return error("invalid range in character class"),
+                                None => return error("character class is not closed"),
+                            }
+                        } else {
+                            start
+                        };
+                        items.push(ClassItem::Range(start, end));
+                    }
+                    Atom::Class(items, negated)
+                }
+                c => Atom::Char(c),
+            };
+            nodes.push(Node::Repeat(atom, 1, 1));
+        }
+
+        Ok(Regex { nodes })
+    }
+
+    // Returns end position of match starting at `start`. `failed` remembers pairs of node index
+    // and position which never match. Without it, backtracking on patterns such as `\w*\w*\w*x`
+    // takes exponential time. With it, each pair is tried at most once
+    fn match_at(&self, chars: &[char], start: usize, failed: &mut [bool]) -> Option<usize> {
+        fn match_nodes(
+            nodes: &[Node],
+            n: usize,
+            chars: &[char],
+            i: usize,
+            failed: &mut [bool],
+        ) -> Option<usize> {
+            let node = match nodes.get(n) {
+                Some(node) => node,
+                None => return Some(i),
+            };
+            let memo = n * (chars.len() + 1) + i;
+            if failed[memo] {
+                return None;
+            }
+            let end = match node {
+                Node::LineStart if i == 0 => match_nodes(nodes, n + 1, chars, i, failed),
+                Node::LineEnd if i == chars.len() => match_nodes(nodes, n + 1, chars, i, failed),
+                Node::WordBoundary => {
+                    let before = i > 0 && is_word_char(chars[i - 1]);
+                    let after = i < chars.len() && is_word_char(chars[i]);
+                    if before != after {
+                        match_nodes(nodes, n + 1, chars, i, failed)
+                    } else {
+                        None
+                    }
+                }
+                Node::Repeat(atom, min, max) => {
+                    // Greedy match. Backtrack from the longest repetition
+                    let count = chars[i..]
+                        .iter()
+                        .take(*max)
+                        .take_while(|c| atom.matches(**c))
+                        .count();
+                    (*min..=count)
+                        .rev()
+                        .find_map(|len| match_nodes(nodes, n + 1, chars, i + len, failed))
+                }
+                _ => None,
+            };
+            if end.is_none() {
+                failed[memo] = true;
+            }
+            end
+        }
+        match_nodes(&self.nodes, 0, chars, start, failed)
+    }
+
+    // Find non-empty matches in the line as (start, end) character indices. `end` is exclusive
+    pub fn find_iter(&self, line: &str) -> Vec<(usize, usize)> {
+        let chars: Vec<char> = line.chars().collect();
+        // Failures do not depend on where the match started so they are shared by all starts
+        let mut failed = vec![false; self.nodes.len() * (chars.len() + 1)];
+        let mut matches = vec![];
+        let mut start = 0;
+        while start <= chars.len() {
+            match self.match_at(&chars, start, &mut failed) {
+                Some(end) if end > start => {
+                    matches.push((start, end));
+                    start = end;
+                }
+                _ => start += 1,
+            }
+        }
+        matches
+    }
+}
+
+// Find all matches in lines as (y, start, end). They can be passed to highlighting as match regions
+pub fn find_matches<'a, I>(regex: &Regex, lines: I) -> Vec<(usize, usize, usize)>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    lines
+        .into_iter()
+        .enumerate()
+        .flat_map(|(y, line)| {
+            regex
+                .find_iter(line)
+                .into_iter()
+                .map(move |(start, end)| (y, start, end))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(pattern: &str, line: &str) -> Vec<(usize, usize)> {
+        Regex::new(pattern).unwrap().find_iter(line)
+    }
+
+    #[test]
+    fn find_rust_functions() {
+        let lines = [
+            "pub fn new() -> Self {",
+            "    // fnord is not a function",
+            "fn  main() { fn_call(); }",
+            "",
+        ];
+        let regex = Regex::new(r"\bfn\s+\w+").unwrap();
+        assert_eq!(
+            find_matches(&regex, lines.iter().copied()),
+            vec![(0, 4, 10), (2, 0, 8)],
+        );
+    }
+
+    #[test]
+    fn find_patterns() {
+        assert_eq!(find("a", "banana"), vec![(1, 2), (3, 4), (5, 6)]);
+        assert_eq!(find("an+a", "bannnana"), vec![(1, 6)]);
+        assert_eq!(
+            find("colou?r", "color colour colouur"),
+            vec![(0, 5), (6, 12)]
+        );
+        assert_eq!(find("x*", "axxb"), vec![(1, 3)]);
+        assert_eq!(find("a.c", "abc a.c ac"), vec![(0, 3), (4, 7)]);
+        assert_eq!(find(r"a\.c", "abc a.c"), vec![(4, 7)]);
+        assert_eq!(find("^ab", "abab"), vec![(0, 2)]);
+        assert_eq!(find("ab$", "abab"), vec![(2, 4)]);
+        assert_eq!(find(r"\d+", "x = 123 + 4"), vec![(4, 7), (10, 11)]);
+        assert_eq!(find("[a-c_]+", "xab_cd"), vec![(1, 5)]);
+        assert_eq!(find("[^a-z ]+", "ab 12 CD"), vec![(3, 5), (6, 8)]);
+        assert_eq!(find("[+-]", "1+2-3"), vec![(1, 2), (3, 4)]);
+        assert_eq!(find(r"[\d.]+", "v1.20"), vec![(1, 5)]);
+        assert_eq!(find("い+", "あいいう"), vec![(1, 3)]);
+        assert_eq!(find(r"\bis\b", "this is"), vec![(5, 7)]);
+        assert_eq!(find("a+b", "aaac"), vec![]);
+    }
+
+    #[test]
+    fn pathological_backtracking() {
+        // Nested backtracking would try a huge number of ways to split the line without memoizing
+        // failures
+        let line = "a".repeat(200);
+        assert_eq!(find(r"\w*\w*\w*\w*\w*\w*x", &line), vec![]);
+        assert_eq!(find(r"\w*\w*\w*\w*\w*\w*a$", &line), vec![(0, 200)]);
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        for pattern in &[
+            "*a", "a**", "(a)", "a|b", "[abc", "[z-a]", r"\q", "a\\", "a{2}",
+        ] {
+            match Regex::new(pattern) {
+                Err(Error::InvalidRegex(_)) => {}
+                r => panic!("unexpected result for {:?}: {:?}", pattern, r),
+            }
+        }
+    }
+}
@@ -0,0 +1,365 @@
+// Parser for syntax definition files. Only a small subset of TOML is supported since a syntax
+// definition consists of flat key-value pairs:
+//
+//   name = "toy"
+//   file_extensions = ["toy"]
+//   line_comment = "#"
+//   keywords = [
+//       "let", "fn",
+//   ]
+//   number = true
+use crate::error::{Error, Result};
+use crate::highlight::SyntaxHighlight;
+use crate::language::Language;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+#[derive(Debug, PartialEq)]
+pub enum Value {
+    Str(String),
+    Bool(bool),
+    Array(Vec<String>),
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Str(_) => "string",
+            Value::Bool(_) => "boolean",
+            Value::Array(_) => "array",
+        }
+    }
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T, S: Into<String>>(&self, msg: S) -> Result<T> {
+        Err(Error::InvalidSyntaxFile(self.line, msg.into()))
+    }
+
+    // Skip whitespaces, comments and (when `newline` is true) newlines
+    fn skip_blank(&mut self, newline: bool) {
+        loop {
+            self.src = self.src.trim_start_matches([' ', '\t']);
+            if self.src.starts_with('#') {
+                let end = self.src.find('\n').unwrap_or(self.src.len());
+                self.src = &self.src[end..];
+            } else if newline && self.src.starts_with('\n') {
+                self.src = &self.src[1..];
+                self.line += 1;
+            } else if newline && self.src.starts_with("\r\n") {
+                self.src = &self.src[2..];
+                self.line += 1;
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.src.starts_with(c) {
+            self.src = &self.src[c.len_utf8()..];
+            true
+        } else {
+            false
+        }
+    }
+
+    fn key(&mut self) -> Result<String> {
+        let len = self
+            .src
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '-')
+            .unwrap_or(self.src.len());
+        if len == 0 {
+            return self.error("key is expected");
+        }
+        let key = self.src[..len].to_string();
+        self.src = &self.src[len..];
+        Ok(key)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let quote = if self.eat('"') {
+            '"'
+        } else if self.eat('\'') {
+            '\'' // Literal string. Escape sequences are not processed
+        } else {
+            return self.error("string is expected");
+        };
+
+        let mut s = String::new();
+        let mut chars = self.src.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\n' => break,
+                c if c == quote => {
+                    self.src = &self.src[i + 1..];
+                    return Ok(s);
+                }
+                '\\' if quote == '"' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('\\') => s.push('\\'),
+                    Some('"') => s.push('"'),
+                    Some(c) => return self.error(format!("unknown escape sequence \\{}", c)),
+                    None => break,
+                },
+                c => s.push(c),
+            }
+        }
+        self.error("string is not closed")
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        if self.eat('[') {
+            let mut elems = vec![];
+            loop {
+                self.skip_blank(true);
+                if self.eat(']') {
+                    return Ok(Value::Array(elems));
+                }
+                elems.push(self.string()?);
+                self.skip_blank(true);
+                if !self.eat(',') {
+                    self.skip_blank(true);
+                    if self.eat(']') {
+                        return Ok(Value::Array(elems));
+                    }
+                    return self.error("',' or ']' is expected in array");
+                }
+            }
+        }
+
+        for (word, b) in &[("true", true), ("false", false)] {
+            if let Some(rest) = self.src.strip_prefix(word) {
+                self.src = rest;
+                return Ok(Value::Bool(*b));
+            }
+        }
+
+        self.string().map(Value::Str)
+    }
+
+    fn parse(mut self) -> Result<Vec<(usize, String, Value)>> {
+        let mut pairs = vec![];
+        loop {
+            self.skip_blank(true);
+            if self.src.is_empty() {
+                return Ok(pairs);
+            }
+            if self.src.starts_with('[') {
+                return self.error("tables are not supported");
+            }
+
+            let line = self.line;
+            let key = self.key()?;
+            self.skip_blank(false);
+            if !self.eat('=') {
+                return self.error(format!("'=' is expected after key '{}'", key));
+            }
+            self.skip_blank(false);
+            let value = self.value()?;
+            self.skip_blank(false);
+            if !self.src.is_empty() && !self.src.starts_with('\n') && !self.src.starts_with("\r\n")
+            {
+                return self.error(format!("newline is expected after value of '{}'", key));
+            }
+            pairs.push((line, key, value));
+        }
+    }
+}
+
+fn parse(src: &str) -> Result<Vec<(usize, String, Value)>> {
+    Parser { src, line: 1 }.parse()
+}
+
+struct CustomSyntax {
+    name: &'static str,
+    file_exts: Vec<String>,
+    syntax: Arc<SyntaxHighlight>,
+}
+
+// Syntax definitions loaded at runtime. A replaced definition is dropped when no buffer uses it
+static CUSTOM_SYNTAXES: Mutex<Vec<CustomSyntax>> = Mutex::new(Vec::new());
+
+fn syntaxes() -> MutexGuard<'static, Vec<CustomSyntax>> {
+    CUSTOM_SYNTAXES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn find_syntax(name: &str) -> Option<Arc<SyntaxHighlight>> {
+    syntaxes()
+        .iter()
+        .find(|s| s.name == name)
+        .map(|s| s.syntax.clone())
+}
+
+pub fn find_lang_by_ext(ext: &str) -> Option<Language> {
+    syntaxes()
+        .iter()
+        .find(|s| s.file_exts.iter().any(|e| e == ext))
+        .map(|s| Language::Custom(s.name))
+}
+
+// Returns the name with static lifetime. The name leaked for a registered syntax is reused so that
+// registering the same language repeatedly does not leak memory
+pub fn intern_name(name: &str) -> &'static str {
+    match syntaxes().iter().find(|s| s.name == name) {
+        Some(s) => s.name,
+        None => Box::leak(name.to_string().into_boxed_str()),
+    }
+}
+
+// Register syntax for `Language::Custom`. When a syntax with the same name was already registered,
+// it is replaced.
+pub fn register_syntax(syntax: SyntaxHighlight, file_exts: Vec<String>) -> Language {
+    let lang = syntax.lang();
+    let name = lang.name();
+    let custom = CustomSyntax {
+        name,
+        file_exts,
+        syntax: Arc::new(syntax),
+    };
+    let mut syntaxes = syntaxes();
+    match syntaxes.iter().position(|s| s.name == name) {
+        Some(i) => syntaxes[i] = custom,
+        None => syntaxes.push(custom),
+    }
+    lang
+}
+
+// Load syntax definition from TOML source and register it. When a definition with the same name
+// was already loaded, it is replaced.
+pub fn load_syntax(src: &str) -> Result<Language> {
+    let mut name = None;
+    let mut file_exts = vec![];
+    let mut pairs = vec![];
+    for (line, key, value) in parse(src)? {
+        match (key.as_str(), value) {
+            ("name", Value::Str(s)) if !s.is_empty() => name = Some(s),
+            ("file_extensions", Value::Array(a)) => file_exts = a,
+            ("name", _) | ("file_extensions", _) => {
+                return Err(Error::InvalidSyntaxFile(
+                    line,
+                    format!("invalid value for '{}'", key),
+                ))
+            }
+            (_, value) => pairs.push((line, key, value)),
+        }
+    }
+    let name = match name {
+        Some(name) => name,
+        None => return Err(Error::InvalidSyntaxFile(1, "'name' is missing".to_string())),
+    };
+
+    let syntax = SyntaxHighlight::from_definition(&name, pairs)?;
+    Ok(register_syntax(syntax, file_exts))
+}
+
+pub fn load_syntax_file<P: AsRef<Path>>(path: P) -> Result<Language> {
+    load_syntax(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_key_values() {
+        let src = r#"
+# Syntax definition
+name = "toy" # comment
+number = true
+character=false
+quotes = ["\"", '\']
+keywords = [
+    "let",
+    "fn", # comment in array
+]
+empty = []
+"#;
+        let pairs = parse(src).unwrap();
+        let expected = [
+            ("name", Value::Str("toy".to_string())),
+            ("number", Value::Bool(true)),
+            ("character", Value::Bool(false)),
+            (
+                "quotes",
+                Value::Array(vec!["\"".to_string(), "\\".to_string()]),
+            ),
+            (
+                "keywords",
+                Value::Array(vec!["let".to_string(), "fn".to_string()]),
+            ),
+            ("empty", Value::Array(vec![])),
+        ];
+        let pairs: Vec<_> = pairs.iter().map(|(_, k, v)| (k.as_str(), v)).collect();
+        let expected: Vec<_> = expected.iter().map(|(k, v)| (*k, v)).collect();
+        assert_eq!(pairs, expected);
+    }
+
+    #[test]
+    fn parse_error() {
+        for (src, line) in &[
+            ("name", 1),
+            ("\nname = \"foo", 2),
+            ("[table]", 1),
+            ("a = [\"x\" \"y\"]", 1),
+            ("a = 1", 1),
+            ("a = true b = false", 1),
+        ] {
+            match parse(src) {
+                Err(Error::InvalidSyntaxFile(l, _)) => assert_eq!(l, *line, "{:?}", src),
+                r => panic!("unexpected result for {:?}: {:?}", src, r),
+            }
+        }
+    }
+
+    #[test]
+    fn load_invalid_definition() {
+        for (src, line) in &[
+            ("keywords = [\"if\"]", 1),
+            ("name = \"bad\"\nunknown = true", 2),
+            ("name = \"bad\"\nnumber = \"yes\"", 2),
+            ("name = \"bad\"\nstring_quotes = [\"ab\"]", 2),
+            ("name = \"bad\"\nblock_comment = [\"/*\"]", 2),
+        ] {
+            match load_syntax(src) {
+                Err(Error::InvalidSyntaxFile(l, _)) => assert_eq!(l, *line, "{:?}", src),
+                r => panic!("unexpected result for {:?}: {:?}", src, r.map(|l| l.name())),
+            }
+        }
+        assert!(find_syntax("bad").is_none());
+    }
+
+    #[test]
+    fn failed_load_registers_nothing() {
+        let src = "name = \"bad-load\"\nfile_extensions = [\"badload\"]\nnumber = \"yes\"";
+        assert!(load_syntax(src).is_err());
+        assert!(find_syntax("bad-load").is_none());
+        assert_eq!(find_lang_by_ext("badload"), None);
+        assert_eq!(Language::detect("foo.badload"), Language::Plain);
+    }
+
+    #[test]
+    fn load_and_find_by_ext() {
+        let lang = load_syntax("name = \"toy-ext\"\nfile_extensions = [\"toyext\"]").unwrap();
+        assert_eq!(lang, Language::Custom("toy-ext"));
+        assert_eq!(lang.name(), "toy-ext");
+        assert_eq!(find_lang_by_ext("toyext"), Some(lang));
+        assert_eq!(Language::detect("foo.toyext"), lang);
+        assert!(find_syntax("toy-ext").is_some());
+    }
+
+    #[test]
+    fn reload_drops_previous_definition() {
+        let lang = load_syntax("name = \"toy-reload\"\nkeywords = [\"a\"]").unwrap();
+        let old = Arc::downgrade(&find_syntax("toy-reload").unwrap());
+        assert_eq!(load_syntax("name = \"toy-reload\"").unwrap(), lang);
+        assert!(old.upgrade().is_none());
+    }
+}
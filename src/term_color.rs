@@ -1,7 +1,8 @@
+use std::borrow::Cow;
 use std::env;
 use term::terminfo::TermInfo;
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum Color {
     Reset,
     Red,
@@ -17,12 +18,113 @@ pub enum Color {
     OrangeBG,
     NonText,
     Invert,
+    Rgb(u8, u8, u8), // Foreground color of 24-bit RGB
+    Palette(u8),     // Foreground color of 256 colors palette
 }
 
 impl Color {
     pub fn has_bg_color(self) -> bool {
         use Color::*;
-        matches!(self, YellowBG | RedBG | OrangeBG)
+        matches!(self, YellowBG | RedBG | OrangeBG | Invert) // Invert swaps background color
+    }
+
+    pub fn bold(self) -> Style {
+        Style::from(self).bold()
+    }
+
+    pub fn dim(self) -> Style {
+        Style::from(self).dim()
+    }
+
+    pub fn underline(self) -> Style {
+        Style::from(self).underline()
+    }
+
+    // Foreground color as RGB. Values are the same as 24-bit colors sequences below
+    pub fn rgb(self) -> (u8, u8, u8) {
+        use Color::*;
+        match self {
+            Reset | RedBG => (0xfb, 0xf1, 0xc7),
+            Red => (0xfb, 0x49, 0x34),
+            Green => (0xb8, 0xbb, 0x26),
+            Gray => (0xa8, 0x99, 0x84),
+            Yellow => (0xfa, 0xbd, 0x2f),
+            Orange => (0xfe, 0x80, 0x19),
+            Blue => (0x83, 0xa5, 0x98),
+            Purple => (0xd3, 0x86, 0x9b),
+            Cyan => (0x8e, 0xc0, 0x7c),
+            YellowBG | OrangeBG | Invert => (0x28, 0x28, 0x28),
+            NonText => (0x66, 0x5c, 0x54),
+            Rgb(r, g, b) => (r, g, b),
+            Palette(n) if n < 16 => {
+                // Colors of xterm's default system colors
+                const SYSTEM: [(u8, u8, u8); 16] = [
+                    (0x00, 0x00, 0x00),
+                    (0xcd, 0x00, 0x00),
+                    (0x00, 0xcd, 0x00),
+                    (0xcd, 0xcd, 0x00),
+                    (0x00, 0x00, 0xee),
+                    (0xcd, 0x00, 0xcd),
+                    (0x00, 0xcd, 0xcd),
+                    (0xe5, 0xe5, 0xe5),
+                    (0x7f, 0x7f, 0x7f),
+                    (0xff, 0x00, 0x00),
+                    (0x00, 0xff, 0x00),
+                    (0xff, 0xff, 0x00),
+                    (0x5c, 0x5c, 0xff),
+                    (0xff, 0x00, 0xff),
+                    (0x00, 0xff, 0xff),
+                    (0xff, 0xff, 0xff),
+                ];
+                SYSTEM[n as usize]
+            }
+            Palette(n) if n < 232 => {
+                let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+                let c = n - 16;
+                (level(c / 36), level(c / 6 % 6), level(c % 6))
+            }
+            Palette(n) => {
+                let v = 8 + (n - 232) * 10;
+                (v, v, v)
+            }
+        }
+    }
+}
+
+// Color with text attributes
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Style {
+    pub color: Color,
+    pub bold: bool,
+    pub dim: bool,
+    pub underline: bool,
+}
+
+impl From<Color> for Style {
+    fn from(color: Color) -> Style {
+        Style {
+            color,
+            bold: false,
+            dim: false,
+            underline: false,
+        }
+    }
+}
+
+impl Style {
+    pub fn bold(mut self) -> Style {
+        self.bold = true;
+        self
+    }
+
+    pub fn dim(mut self) -> Style {
+        self.dim = true;
+        self
+    }
+
+    pub fn underline(mut self) -> Style {
+        self.underline = true;
+        self
     }
 }
 
@@ -70,6 +172,9 @@ fn true_colors_sequence(color: Color) -> &'static [u8] {
         .as_bytes(),
         NonText => rgb_color!(fg, 0x66, 0x5c, 0x54).as_bytes(),
         Invert => b"\x1b[7m",
+        Rgb(..) | Palette(_) => {
+            unreachable!("sequence for RGB or palette color is built dynamically")
+        }
     }
 }
 
@@ -102,6 +207,9 @@ fn colors_256_sequence(color: Color) -> &'static [u8] {
         OrangeBG => b"\x1b[38;5;235m\x1b[48;5;166m",
         NonText => b"\x1b[38;5;241m",
         Invert => b"\x1b[7m",
+        Rgb(..) | Palette(_) => {
+            unreachable!("sequence for RGB or palette color is built dynamically")
+        }
     }
 }
 
@@ -123,6 +231,46 @@ fn colors_16_sequence(color: Color) -> &'static [u8] {
         OrangeBG => b"\x1b[107m\x1b[30m", // White BG color is used instead of orange
         NonText => b"\x1b[37m",
         Invert => b"\x1b[7m",
+        Rgb(..) | Palette(_) => {
+            unreachable!("sequence for RGB or palette color is built dynamically")
+        }
+    }
+}
+
+// Fallback for terminals which don't support 24-bit colors. Each channel is mapped to 6x6x6 color
+// cube of 256 colors or to nearest 8 colors (with bright variants) of 16 colors
+fn rgb_sequence(term: TermColor, r: u8, g: u8, b: u8) -> Vec<u8> {
+    match term {
+        TermColor::TrueColors => format!("\x1b[38;2;{};{};{}m", r, g, b).into_bytes(),
+        TermColor::Colors256 => {
+            let level = |v: u8| (v as u16 * 5 + 127) / 255;
+            let n = 16 + 36 * level(r) + 6 * level(g) + level(b);
+            format!("\x1b[38;5;{}m", n).into_bytes()
+        }
+        TermColor::Colors16 => {
+            let bits = (r > 127) as u8 | ((g > 127) as u8) << 1 | ((b > 127) as u8) << 2;
+            let base = if r.max(g).max(b) > 191 { 90 } else { 30 };
+            format!("\x1b[{}m", base + bits).into_bytes()
+        }
+    }
+}
+
+// 16 colors terminal can show only first 16 colors of the palette. Others are approximated from RGB
+// values of 6x6x6 color cube or grayscale ramp
+fn palette_sequence(term: TermColor, n: u8) -> Vec<u8> {
+    match term {
+        TermColor::TrueColors | TermColor::Colors256 => format!("\x1b[38;5;{}m", n).into_bytes(),
+        TermColor::Colors16 if n < 8 => format!("\x1b[{}m", 30 + n).into_bytes(),
+        TermColor::Colors16 if n < 16 => format!("\x1b[{}m", 90 + n - 8).into_bytes(),
+        TermColor::Colors16 if n < 232 => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let c = n - 16;
+            rgb_sequence(term, level(c / 36), level(c / 6 % 6), level(c % 6))
+        }
+        TermColor::Colors16 => {
+            let v = 8 + (n - 232) * 10;
+            rgb_sequence(term, v, v, v)
+        }
     }
 }
 
@@ -158,11 +306,115 @@ impl TermColor {
             .unwrap_or(TermColor::Colors16)
     }
 
-    pub fn sequence(self, color: Color) -> &'static [u8] {
+    pub fn sequence(self, color: Color) -> Cow<'static, [u8]> {
+        match color {
+            Color::Rgb(r, g, b) => return rgb_sequence(self, r, g, b).into(),
+            Color::Palette(n) => return palette_sequence(self, n).into(),
+            _ => {}
+        }
         match self {
-            TermColor::TrueColors => true_colors_sequence(color),
-            TermColor::Colors256 => colors_256_sequence(color),
-            TermColor::Colors16 => colors_16_sequence(color),
+            TermColor::TrueColors => true_colors_sequence(color).into(),
+            TermColor::Colors256 => colors_256_sequence(color).into(),
+            TermColor::Colors16 => colors_16_sequence(color).into(),
+        }
+    }
+}
+
+impl TermColor {
+    // Sequence to change style of text printed after from `prev` to `next`. Attributes are cleared
+    // with '\x1b[22m' (normal intensity) since color sequences don't reset them except for Reset
+    pub fn switch_sequence(self, prev: Style, next: Style) -> Vec<u8> {
+        let mut seq = vec![];
+        if prev.color.has_bg_color() {
+            seq.extend_from_slice(&self.sequence(Color::Reset));
+        } else if (prev.bold && !next.bold) || (prev.dim && !next.dim) {
+            seq.extend_from_slice(b"\x1b[22m");
         }
+        if prev.underline && !next.underline && !prev.color.has_bg_color() {
+            seq.extend_from_slice(b"\x1b[24m");
+        }
+        seq.extend_from_slice(&self.sequence(next.color));
+        if next.bold {
+            seq.extend_from_slice(b"\x1b[1m");
+        }
+        if next.dim {
+            seq.extend_from_slice(b"\x1b[2m");
+        }
+        if next.underline {
+            seq.extend_from_slice(b"\x1b[4m");
+        }
+        seq
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgb_sequence() {
+        let red = Color::Rgb(255, 0, 0);
+        assert_eq!(&*TermColor::TrueColors.sequence(red), b"\x1b[38;2;255;0;0m");
+        assert_eq!(&*TermColor::Colors256.sequence(red), b"\x1b[38;5;196m");
+        assert_eq!(&*TermColor::Colors16.sequence(red), b"\x1b[91m");
+        assert_eq!(
+            &*TermColor::Colors16.sequence(Color::Rgb(0, 0, 100)),
+            b"\x1b[30m",
+        );
+    }
+
+    #[test]
+    fn palette_sequence() {
+        let blue = Color::Palette(33);
+        assert_eq!(&*TermColor::TrueColors.sequence(blue), b"\x1b[38;5;33m");
+        assert_eq!(&*TermColor::Colors256.sequence(blue), b"\x1b[38;5;33m");
+        assert_eq!(
+            &*TermColor::Colors16.sequence(Color::Palette(1)),
+            b"\x1b[31m"
+        );
+        assert_eq!(
+            &*TermColor::Colors16.sequence(Color::Palette(9)),
+            b"\x1b[91m"
+        );
+        assert_eq!(
+            &*TermColor::Colors16.sequence(Color::Palette(196)),
+            b"\x1b[91m"
+        );
+        assert_eq!(
+            &*TermColor::Colors256.sequence(Color::Reset),
+            b"\x1b[39;0m\x1b[38;5;230m\x1b[48;5;235m",
+        );
+    }
+
+    #[test]
+    fn named_color_sequence() {
+        assert_eq!(&*TermColor::Colors16.sequence(Color::Red), b"\x1b[91m");
+    }
+
+    #[test]
+    fn style_sequence() {
+        let term = TermColor::Colors16;
+        let normal = Style::from(Color::Reset);
+        let keyword = Color::Blue.bold();
+        assert_eq!(term.switch_sequence(normal, keyword), b"\x1b[94m\x1b[1m");
+        assert_eq!(
+            term.switch_sequence(keyword, Color::Orange.into()),
+            b"\x1b[22m\x1b[33m",
+        );
+        assert_eq!(
+            term.switch_sequence(keyword, Color::Gray.dim()),
+            b"\x1b[22m\x1b[90m\x1b[2m",
+        );
+        assert_eq!(
+            term.switch_sequence(Color::YellowBG.into(), keyword),
+            b"\x1b[39;0m\x1b[94m\x1b[1m",
+        );
+        assert_eq!(term.switch_sequence(keyword, normal), b"\x1b[22m\x1b[39;0m");
+        let link = Color::Blue.underline();
+        assert_eq!(term.switch_sequence(normal, link), b"\x1b[94m\x1b[4m");
+        assert_eq!(
+            term.switch_sequence(link, Color::Gray.into()),
+            b"\x1b[24m\x1b[90m",
+        );
     }
 }
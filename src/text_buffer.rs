@@ -1,7 +1,7 @@
 use crate::edit_diff::{EditDiff, UndoRedo};
 use crate::error::Result;
 use crate::history::History;
-use crate::language::{Indent, Language};
+use crate::language::{Indent, Language, DETECT_CONTENT_LINES, MODELINE_LINES};
 use crate::row::Row;
 use std::cmp;
 use std::fs::File;
@@ -58,6 +58,34 @@ impl<'a> Iterator for Lines<'a> {
 
 impl<'a> ExactSizeIterator for Lines<'a> {}
 
+// When file extension is unknown, fall back to modeline, shebang line and then to file content
+fn detect_lang(path: &Path, rows: &[Row]) -> Language {
+    match Language::from_file_path(path) {
+        Language::Plain => {
+            // Modelines are written in the first or last lines
+            let head = rows.iter().take(MODELINE_LINES);
+            let tail = rows.iter().skip(MODELINE_LINES).rev().take(MODELINE_LINES);
+            let lines: Vec<_> = head.chain(tail).map(|r| r.buffer()).collect();
+            Language::detect_from_modeline(&lines)
+                .or_else(|| {
+                    rows.first()
+                        .and_then(|r| Language::detect_from_shebang(r.buffer()))
+                })
+                .or_else(|| {
+                    let text = rows
+                        .iter()
+                        .take(DETECT_CONTENT_LINES)
+                        .map(|r| r.buffer())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    Language::detect_from_content(&text)
+                })
+                .unwrap_or(Language::Plain)
+        }
+        lang => lang,
+    }
+}
+
 pub struct TextBuffer {
     // (x, y) coordinate in internal text buffer of rows
     cx: usize,
@@ -83,6 +111,8 @@ pub struct TextBuffer {
     // Flag to require screen update
     // TODO: Merge with Screen's dirty_start field by using RenderContext struct
     dirty_start: Option<usize>,
+    // Last line modified by edit. Lines between dirty_start and dirty_end were modified
+    dirty_end: usize,
 }
 
 impl TextBuffer {
@@ -98,6 +128,7 @@ impl TextBuffer {
             history: History::default(),
             inserted_undo: false,
             dirty_start: Some(0), // Ensure to render first screen
+            dirty_end: 0,
         }
     }
 
@@ -113,6 +144,7 @@ impl TextBuffer {
             history: History::default(),
             inserted_undo: false,
             dirty_start: Some(0), // Ensure to render first screen
+            dirty_end: 0,
         })
     }
 
@@ -129,10 +161,11 @@ impl TextBuffer {
             return Ok(buf);
         }
 
-        let row = io::BufReader::new(File::open(path)?)
+        let row: Vec<_> = io::BufReader::new(File::open(path)?)
             .lines()
             .map(|r| Row::new(r?))
             .collect::<Result<_>>()?;
+        let lang = detect_lang(path, &row);
 
         Ok(Self {
             cx: 0,
@@ -141,14 +174,16 @@ impl TextBuffer {
             row,
             undo_count: 0,
             modified: false,
-            lang: Language::detect(path),
+            lang,
             history: History::default(),
             inserted_undo: false,
             dirty_start: Some(0),
+            dirty_end: 0,
         })
     }
 
-    fn set_dirty_start(&mut self, line: usize) {
+    fn set_dirty(&mut self, line: usize) {
+        self.dirty_end = cmp::max(self.dirty_end, line);
         if let Some(l) = self.dirty_start {
             if l <= line {
                 return;
@@ -160,7 +195,7 @@ impl TextBuffer {
     fn apply_diff(&mut self, diff: &EditDiff, which: UndoRedo) {
         let (x, y) = diff.apply(&mut self.row, which);
         self.set_cursor(x, y);
-        self.set_dirty_start(y);
+        self.set_dirty(y);
     }
 
     fn new_diff(&mut self, diff: EditDiff) {
@@ -182,11 +217,15 @@ impl TextBuffer {
     // This method must be called after handling one key input.
     // TODO: This should be replaced with Drop when separating logic to edit text buffer from TextBuffer
     // by introducing RenderContext.
-    pub fn finish_edit(&mut self) -> Option<usize> {
+    // Returns range of modified lines (inclusive) since previous call
+    pub fn finish_edit(&mut self) -> Option<(usize, usize)> {
         self.inserted_undo = false;
-        let dirty_start = self.dirty_start;
+        let dirty = self
+            .dirty_start
+            .map(|start| (start, cmp::max(start, self.dirty_end)));
         self.dirty_start = None;
-        dirty_start
+        self.dirty_end = 0;
+        dirty
     }
 
     pub fn insert_char(&mut self, ch: char) {
@@ -486,7 +525,7 @@ impl TextBuffer {
 
     pub fn set_file<S: Into<String>>(&mut self, file_path: S) {
         let file = FilePath::from_string(file_path);
-        self.lang = Language::detect(&file.path);
+        self.lang = detect_lang(&file.path, &self.row);
         self.file = Some(file);
     }
 
@@ -516,14 +555,14 @@ impl TextBuffer {
         for line in self.row.iter() {
             let b = line.buffer();
             writeln!(f, "{}", b).map_err(|e| format!("Could not write to file: {}", e))?;
-            bytes += b.as_bytes().len() + 1;
+            bytes += b.len() + 1;
         }
         f.flush()
             .map_err(|e| format!("Could not flush to file: {}", e))?;
 
         self.undo_count = 0;
         self.modified = false;
-        Ok(format!("{} bytes written to {}", bytes, &file.display))
+        Ok(format!("{} bytes written to {}", bytes, file.display))
     }
 
     pub fn set_cursor(&mut self, x: usize, y: usize) {
@@ -535,11 +574,12 @@ impl TextBuffer {
         (self.cx, self.cy)
     }
 
-    fn after_undoredo(&mut self, state: Option<(usize, usize, usize, bool)>) -> bool {
+    fn after_undoredo(&mut self, state: Option<(usize, usize, usize, usize, bool)>) -> bool {
         match state {
-            Some((x, y, s, _)) => {
+            Some((x, y, s, e, _)) => {
                 self.set_cursor(x, y);
-                self.set_dirty_start(s);
+                self.set_dirty(s);
+                self.set_dirty(e);
                 true
             }
             None => false,
@@ -548,7 +588,7 @@ impl TextBuffer {
 
     pub fn undo(&mut self) -> bool {
         let state = self.history.undo(&mut self.row);
-        if let Some((_, _, _, edited)) = state {
+        if let Some((_, _, _, _, edited)) = state {
             // If edited is true, it means that undo target is the ongoing change. In the case,
             // undo point is not consumed and undo count should not be decreased
             if !edited {
@@ -561,7 +601,7 @@ impl TextBuffer {
 
     pub fn redo(&mut self) -> bool {
         let state = self.history.redo(&mut self.row);
-        if let Some((_, _, _, edited)) = state {
+        if let Some((_, _, _, _, edited)) = state {
             // If edited is true, it means that redo target is the ongoing change. In the case,
             // redo does not happen since the new ongoing change is happening and undo count should
             // not be incremented